@@ -71,7 +71,15 @@ echo "clip hire initial neck maid actor venue client foam budget lock catalog sw
 PIXEL_INIT='{
   "admin_address": "'"$DEFAULT_DEV_ADDRESS"'",
   "cooldown": 1,
+  "start_height": null,
   "end_height": null,
+  "batch_cooldown": "per_pixel",
+  "max_batch_size": 10,
+  "price": null,
+  "accepted_cw20": [],
+  "history_depth": 0,
+  "allowlist_enabled": false,
+  "clock_mode": "height",
   "width": 10,
   "height": 10
 }'
@@ -83,7 +91,7 @@ $BINARY tx wasm instantiate $CONTRACT_CODE "$PIXEL_INIT" \
 PIXEL_CONTRACT=$($BINARY q wasm list-contract-by-code $CONTRACT_CODE --output json | jq -r '.contracts[-1]')
 
 # Query the initial grid (first chunk)
-GET_GRID='{ "get_chunk": { "x": 0, "y": 0 } }'
+GET_GRID='{ "get_chunk": { "canvas_id": 0, "x": 0, "y": 0 } }'
 
 GRID=$($BINARY query wasm contract-state smart "$PIXEL_CONTRACT" "$GET_GRID" --output json | jq -r .data.grid)
 echo "$GRID" | jq .
@@ -91,11 +99,12 @@ echo "$GRID" | jq .
 # Draw
 DRAW='{
   "draw": {
+    "canvas_id": 0,
     "chunk_x": 0,
     "chunk_y": 0,
     "x": 0,
     "y": 0,
-    "color": "red"
+    "color": 5
   }
 }'
 echo "$DRAW" | jq .
@@ -111,11 +120,12 @@ sleep 6
 # Draw
 DRAW='{
   "draw": {
+    "canvas_id": 0,
     "chunk_x": 0,
     "chunk_y": 0,
     "x": 1,
     "y": 0,
-    "color": "yellow"
+    "color": 8
   }
 }'
 echo "$DRAW" | jq .
@@ -1,25 +1,297 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use cw2::set_contract_version;
+use cosmwasm_std::{
+    from_binary, to_binary, to_vec, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env,
+    Event, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128, WasmMsg,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, Expiration};
+use cw_storage_plus::Bound;
+use semver::Version;
+use serde::Serialize;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
 
 use crate::error::ContractError;
-use crate::msg::{ChunkResponse, CooldownResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Config, Dimensions, PixelInfo, CHUNKS, CONFIG, COOLDOWNS, DIMENSIONS};
+use crate::migrations::{load_chunk, load_legacy_grid, run_migrations, save_chunk, v0_1};
+use crate::msg::{
+    AcceptedCw20, AllowlistResponse, BannedResponse, CanDrawResponse, CanvasInfo, CanvasesResponse,
+    ChunkInfo, ChunkResponse, ChunksResponse, CooldownResponse, DrawRejection, ExecuteMsg,
+    InstantiateMsg, LeaderboardEntry, LeaderboardResponse, LockedRegion, LockedRegionsResponse,
+    MigrateMsg, OperatorRoles, PaletteResponse, PendingAdminResponse, PixelDraw, PixelHistoryEntry,
+    PixelHistoryResponse, QueryMsg, ReceiveMsg, RegionResponse, RolesResponse,
+};
+use crate::state::{
+    intern_painter, BatchCooldown, Canvas, Chunk, Config, Cw20Price, Dimensions, PainterStats,
+    PendingAdmin, PixelHistory, PixelInfo, Region, Role, ALLOWLIST, BANNED, CANVASES, CANVAS_COUNT,
+    CHUNKS, CHUNK_LAST_MODIFIED, CONFIG, COOLDOWNS, CW20_TREASURY, DEFAULT_PALETTE, LOCKED_REGIONS,
+    LOCKED_REGION_COUNT, PAINTER_ADDRESSES, PAINTER_IDS, PAINTER_PIXELS, PAINTER_RANKING,
+    PAINTER_STATS, PALETTE, PENDING_ADMIN, PIXEL_DRAW_COUNT, PIXEL_HISTORY, PIXEL_HISTORY_START,
+    ROLES,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:juno-pixel";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
-const CHUNK_SIZE: u64 = 32;
+const DEFAULT_CHUNK_SIZE: u64 = 32;
+const MAX_CHUNK_SIZE: u64 = 128;
+// settings for pagination
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+// pixels returned by a single region query
+const MAX_REGION_PIXELS: u64 = 4096;
+// pixels of drawn chunks a single resize can move or drop, at least one chunk
+const MAX_RESIZE_PIXELS: u64 = 4096;
+// pixels restored per transaction when rolling back a region
+const DEFAULT_ROLLBACK_LIMIT: u32 = 100;
+const MAX_ROLLBACK_LIMIT: u32 = 500;
+// pixels erased per transaction when banning a painter
+const DEFAULT_ERASE_LIMIT: u32 = 100;
+const MAX_ERASE_LIMIT: u32 = 500;
 
-fn validate_color(color_code: u8) -> Result<(), ContractError> {
-    if color_code > 15 {
+/// The admin holds every role, anyone else needs to have been granted it.
+fn check_role(
+    storage: &dyn Storage,
+    config: &Config,
+    sender: &Addr,
+    role: Role,
+) -> Result<(), ContractError> {
+    if *sender == config.admin_address {
+        return Ok(());
+    }
+
+    let roles = ROLES.may_load(storage, sender)?.unwrap_or_default();
+    if !roles.contains(&role) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+fn validate_color(color_code: u8, palette: &[String]) -> Result<(), ContractError> {
+    if color_code as usize >= palette.len() {
         return Err(ContractError::InvalidColor {});
     }
 
     Ok(())
 }
 
+fn validate_palette(palette: &[String]) -> Result<(), ContractError> {
+    if palette.is_empty() || palette.len() > 256 {
+        return Err(ContractError::InvalidPalette {});
+    }
+    for color in palette {
+        let valid = color.len() == 7
+            && color.starts_with('#')
+            && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+        if !valid {
+            return Err(ContractError::InvalidPalette {});
+        }
+    }
+
+    Ok(())
+}
+
+fn load_canvas(storage: &dyn Storage, canvas_id: u64) -> Result<Canvas, ContractError> {
+    CANVASES
+        .may_load(storage, canvas_id)?
+        .ok_or(ContractError::CanvasNotFound { canvas_id })
+}
+
+fn validate_start_height(
+    start_height: Option<u64>,
+    end_height: Option<u64>,
+) -> Result<(), ContractError> {
+    if let (Some(start_height), Some(end_height)) = (start_height, end_height) {
+        if start_height >= end_height {
+            return Err(ContractError::InvalidStartHeight {});
+        }
+    }
+    Ok(())
+}
+
+fn new_canvas(
+    now: u64,
+    cooldown: u64,
+    start_height: Option<u64>,
+    end_height: Option<u64>,
+    width: u64,
+    height: u64,
+    chunk_size: Option<u64>,
+) -> Result<Canvas, ContractError> {
+    if let Some(end_height) = end_height {
+        if end_height <= now {
+            return Err(ContractError::InvalidEndHeight {});
+        }
+    }
+    validate_start_height(start_height, end_height)?;
+
+    let chunk_size = chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+    if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+        return Err(ContractError::InvalidChunkSize {
+            max: MAX_CHUNK_SIZE,
+        });
+    }
+
+    let dimensions = Dimensions {
+        width,
+        height,
+        chunk_size,
+    };
+    validate_dimensions(&dimensions)?;
+
+    Ok(Canvas {
+        dimensions,
+        cooldown,
+        start_height,
+        end_height,
+    })
+}
+
+/// Checks the canvas has at least one chunk and its pixels can be addressed.
+fn validate_dimensions(dimensions: &Dimensions) -> Result<(), ContractError> {
+    if dimensions.width == 0 || dimensions.height == 0 {
+        return Err(ContractError::InvalidDimensions {});
+    }
+    let pixel_count = dimensions
+        .width
+        .checked_mul(dimensions.chunk_size)
+        .zip(dimensions.height.checked_mul(dimensions.chunk_size))
+        .and_then(|(width, height)| width.checked_mul(height));
+    if pixel_count.is_none() {
+        return Err(ContractError::InvalidDimensions {});
+    }
+    Ok(())
+}
+
+fn validate_region(dimensions: &Dimensions, region: &Region) -> Result<(), ContractError> {
+    let canvas_width = dimensions.width * dimensions.chunk_size;
+    let canvas_height = dimensions.height * dimensions.chunk_size;
+    if region.width == 0
+        || region.height == 0
+        || region.x >= canvas_width
+        || region.y >= canvas_height
+        || region.width > canvas_width - region.x
+        || region.height > canvas_height - region.y
+    {
+        return Err(ContractError::InvalidRegion {});
+    }
+    Ok(())
+}
+
+/// Splits global pixel coordinates into the chunk and the position inside it,
+/// returned as `(chunk_x, chunk_y, x, y)`.
+fn split_coordinates(
+    dimensions: &Dimensions,
+    x: u64,
+    y: u64,
+) -> Result<(u64, u64, u64, u64), ContractError> {
+    let width = dimensions.width * dimensions.chunk_size;
+    let height = dimensions.height * dimensions.chunk_size;
+    if x >= width || y >= height {
+        return Err(ContractError::PixelOutOfBounds {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    let chunk_size = dimensions.chunk_size;
+    Ok((
+        x / chunk_size,
+        y / chunk_size,
+        x % chunk_size,
+        y % chunk_size,
+    ))
+}
+
+fn default_chunk(chunk_size: u64) -> Chunk {
+    Chunk::empty((chunk_size * chunk_size) as usize)
+}
+
+/// Checks the funds sent cover the price of drawing `pixel_count` pixels,
+/// returning whatever was sent on top of that so it can be refunded.
+fn take_payment(
+    config: &Config,
+    funds: &[Coin],
+    pixel_count: u64,
+) -> Result<Vec<Coin>, ContractError> {
+    let mut refund: Vec<Coin> = funds
+        .iter()
+        .filter(|coin| !coin.amount.is_zero())
+        .cloned()
+        .collect();
+
+    if let Some(price) = &config.price {
+        let required = Coin {
+            denom: price.denom.clone(),
+            amount: price
+                .amount
+                .checked_mul(pixel_count.into())
+                .map_err(StdError::from)?,
+        };
+        if !required.amount.is_zero() {
+            match refund.iter_mut().find(|coin| coin.denom == required.denom) {
+                Some(paid) if paid.amount >= required.amount => paid.amount -= required.amount,
+                _ => return Err(ContractError::InsufficientFunds { required }),
+            }
+            refund.retain(|coin| !coin.amount.is_zero());
+        }
+    }
+
+    Ok(refund)
+}
+
+fn validate_batch_size(config: &Config, pixels: &[PixelDraw]) -> Result<(), ContractError> {
+    if pixels.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+    if pixels.len() as u64 > config.max_batch_size {
+        return Err(ContractError::BatchTooLarge {
+            max: config.max_batch_size,
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_accepted_cw20(
+    deps: Deps,
+    accepted_cw20: Vec<AcceptedCw20>,
+) -> Result<Vec<Cw20Price>, ContractError> {
+    accepted_cw20
+        .into_iter()
+        .map(|accepted| {
+            Ok(Cw20Price {
+                token: deps.api.addr_validate(&accepted.token)?,
+                price: accepted.price,
+            })
+        })
+        .collect()
+}
+
+fn cw20_transfer(token: &Addr, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    }))
+}
+
+fn add_refund(response: Response, recipient: &Addr, refund: Vec<Coin>) -> Response {
+    if refund.is_empty() {
+        return response;
+    }
+
+    response.add_message(BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount: refund,
+    })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -30,25 +302,42 @@ pub fn instantiate(
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     let admin_address = deps.api.addr_validate(&msg.admin_address)?;
+    let accepted_cw20 = validate_accepted_cw20(deps.as_ref(), msg.accepted_cw20)?;
+    let canvas = new_canvas(
+        msg.clock_mode.now(&env.block),
+        msg.cooldown,
+        msg.start_height,
+        msg.end_height,
+        msg.width,
+        msg.height,
+        msg.chunk_size,
+    )?;
 
-    if let Some(end_height) = msg.end_height {
-        if end_height <= env.block.height {
-            return Err(ContractError::InvalidEndHeight {});
-        }
-    }
+    let palette = msg.palette.unwrap_or_else(|| {
+        DEFAULT_PALETTE
+            .iter()
+            .map(|color| color.to_string())
+            .collect()
+    });
+    validate_palette(&palette)?;
 
     let config = Config {
         admin_address,
-        cooldown: msg.cooldown,
-        end_height: msg.end_height,
-    };
-    let dimensions = Dimensions {
-        width: msg.width,
-        height: msg.height,
+        batch_cooldown: msg.batch_cooldown,
+        max_batch_size: msg.max_batch_size,
+        price: msg.price,
+        accepted_cw20,
+        history_depth: msg.history_depth,
+        allowlist_enabled: msg.allowlist_enabled,
+        clock_mode: msg.clock_mode,
+        paused: false,
+        pause_reason: None,
     };
 
     CONFIG.save(deps.storage, &config)?;
-    DIMENSIONS.save(deps.storage, &dimensions)?;
+    PALETTE.save(deps.storage, &palette)?;
+    CANVASES.save(deps.storage, 0, &canvas)?;
+    CANVAS_COUNT.save(deps.storage, &1)?;
 
     Ok(Response::new().add_attribute("method", "instantiate"))
 }
@@ -62,20 +351,129 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Draw {
+            canvas_id,
             chunk_x,
             chunk_y,
             x,
             y,
             color,
-        } => execute_draw(deps, env, info, chunk_x, chunk_y, x, y, color),
-        ExecuteMsg::UpdateAdmin { new_admin_address } => {
-            execute_update_admin(deps, env, info, new_admin_address)
+        } => execute_draw(deps, env, info, canvas_id, chunk_x, chunk_y, x, y, color),
+        ExecuteMsg::DrawBatch { canvas_id, pixels } => {
+            execute_draw_batch(deps, env, info, canvas_id, pixels)
+        }
+        ExecuteMsg::DrawAt {
+            canvas_id,
+            x,
+            y,
+            color,
+        } => execute_draw_at(deps, env, info, canvas_id, x, y, color),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::CreateCanvas {
+            cooldown,
+            start_height,
+            end_height,
+            width,
+            height,
+            chunk_size,
+        } => execute_create_canvas(
+            deps,
+            env,
+            info,
+            cooldown,
+            start_height,
+            end_height,
+            width,
+            height,
+            chunk_size,
+        ),
+        ExecuteMsg::ProposeAdmin {
+            new_admin_address,
+            expires,
+        } => execute_propose_admin(deps, env, info, new_admin_address, expires),
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, env, info),
+        ExecuteMsg::GrantRole { address, role } => {
+            execute_grant_role(deps, env, info, address, role)
+        }
+        ExecuteMsg::RevokeRole { address, role } => {
+            execute_revoke_role(deps, env, info, address, role)
+        }
+        ExecuteMsg::UpdateCooldown {
+            canvas_id,
+            new_cooldown,
+        } => execute_update_cooldown(deps, env, info, canvas_id, new_cooldown),
+        ExecuteMsg::UpdateStartHeight {
+            canvas_id,
+            new_start_height,
+        } => execute_update_start_height(deps, env, info, canvas_id, new_start_height),
+        ExecuteMsg::UpdateEndHeight {
+            canvas_id,
+            new_end_height,
+        } => execute_update_end_height(deps, env, info, canvas_id, new_end_height),
+        ExecuteMsg::Resize {
+            canvas_id,
+            width,
+            height,
+            offset_x,
+            offset_y,
+            force,
+        } => execute_resize(
+            deps, env, info, canvas_id, width, height, offset_x, offset_y, force,
+        ),
+        ExecuteMsg::UpdateBatchConfig {
+            new_batch_cooldown,
+            new_max_batch_size,
+        } => execute_update_batch_config(deps, env, info, new_batch_cooldown, new_max_batch_size),
+        ExecuteMsg::UpdatePrice { new_price } => execute_update_price(deps, env, info, new_price),
+        ExecuteMsg::UpdateAcceptedCw20 { new_accepted_cw20 } => {
+            execute_update_accepted_cw20(deps, env, info, new_accepted_cw20)
+        }
+        ExecuteMsg::UpdatePalette { new_palette } => {
+            execute_update_palette(deps, env, info, new_palette)
+        }
+        ExecuteMsg::ExtendPalette { colors } => execute_extend_palette(deps, env, info, colors),
+        ExecuteMsg::UpdateHistoryDepth { new_history_depth } => {
+            execute_update_history_depth(deps, env, info, new_history_depth)
+        }
+        ExecuteMsg::UpdateAllowlistEnabled {
+            new_allowlist_enabled,
+        } => execute_update_allowlist_enabled(deps, env, info, new_allowlist_enabled),
+        ExecuteMsg::UpdateAllowlist { add, remove } => {
+            execute_update_allowlist(deps, env, info, add, remove)
+        }
+        ExecuteMsg::RollbackRegion {
+            canvas_id,
+            rect,
+            to_height,
+            start_after,
+            limit,
+        } => execute_rollback_region(
+            deps,
+            env,
+            info,
+            canvas_id,
+            rect,
+            to_height,
+            start_after,
+            limit,
+        ),
+        ExecuteMsg::LockRegion { canvas_id, region } => {
+            execute_lock_region(deps, env, info, canvas_id, region)
         }
-        ExecuteMsg::UpdateCooldown { new_cooldown } => {
-            execute_update_cooldown(deps, env, info, new_cooldown)
+        ExecuteMsg::UnlockRegion {
+            canvas_id,
+            region_id,
+        } => execute_unlock_region(deps, env, info, canvas_id, region_id),
+        ExecuteMsg::BanPainter { address, erase } => {
+            execute_ban_painter(deps, env, info, address, erase)
         }
-        ExecuteMsg::UpdateEndHeight { new_end_height } => {
-            execute_update_end_height(deps, env, info, new_end_height)
+        ExecuteMsg::UnbanPainter { address } => execute_unban_painter(deps, env, info, address),
+        ExecuteMsg::ErasePainter { address, limit } => {
+            execute_erase_painter(deps, env, info, address, limit)
+        }
+        ExecuteMsg::Pause { reason } => execute_pause(deps, env, info, reason),
+        ExecuteMsg::Unpause {} => execute_unpause(deps, env, info),
+        ExecuteMsg::WithdrawTreasury { recipient } => {
+            execute_withdraw_treasury(deps, env, info, recipient)
         }
     }
 }
@@ -85,6 +483,7 @@ pub fn execute_draw(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    canvas_id: u64,
     chunk_x: u64,
     chunk_y: u64,
     x: u64,
@@ -92,142 +491,2213 @@ pub fn execute_draw(
     color: u8,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let dimensions = DIMENSIONS.load(deps.storage)?;
+    let refund = take_payment(&config, &info.funds, 1)?;
+
+    let pixel = PixelDraw {
+        chunk_x,
+        chunk_y,
+        x,
+        y,
+        color,
+    };
+    let events = draw_pixels(deps, env, canvas_id, &info.sender, vec![pixel])?;
+
+    let response = Response::new()
+        .add_attribute("action", "draw")
+        .add_events(events);
+    Ok(add_refund(response, &info.sender, refund))
+}
+
+pub fn execute_draw_at(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    canvas_id: u64,
+    x: u64,
+    y: u64,
+    color: u8,
+) -> Result<Response, ContractError> {
+    let dimensions = load_canvas(deps.storage, canvas_id)?.dimensions;
+    let (chunk_x, chunk_y, x, y) = split_coordinates(&dimensions, x, y)?;
+
+    execute_draw(deps, env, info, canvas_id, chunk_x, chunk_y, x, y, color)
+}
+
+pub fn execute_draw_batch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    canvas_id: u64,
+    pixels: Vec<PixelDraw>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    validate_batch_size(&config, &pixels)?;
+
+    let count = pixels.len();
+    let refund = take_payment(&config, &info.funds, count as u64)?;
+    let events = draw_pixels(deps, env, canvas_id, &info.sender, pixels)?;
+
+    let response = Response::new()
+        .add_attribute("action", "draw_batch")
+        .add_attribute("pixels", count.to_string())
+        .add_events(events);
+    Ok(add_refund(response, &info.sender, refund))
+}
+
+pub fn execute_receive(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // The sender is the token contract, the painter is whoever sent the tokens
+    let token = info.sender;
+    let accepted = config
+        .accepted_cw20
+        .iter()
+        .find(|accepted| accepted.token == token)
+        .ok_or(ContractError::UnacceptedToken {})?;
+    let painter = deps.api.addr_validate(&wrapper.sender)?;
+
+    let (action, canvas_id, pixels) = match from_binary(&wrapper.msg)? {
+        ReceiveMsg::Draw {
+            canvas_id,
+            chunk_x,
+            chunk_y,
+            x,
+            y,
+            color,
+        } => (
+            "draw",
+            canvas_id,
+            vec![PixelDraw {
+                chunk_x,
+                chunk_y,
+                x,
+                y,
+                color,
+            }],
+        ),
+        ReceiveMsg::DrawBatch { canvas_id, pixels } => {
+            validate_batch_size(&config, &pixels)?;
+            ("draw_batch", canvas_id, pixels)
+        }
+    };
+
+    let count = pixels.len();
+    let required = accepted
+        .price
+        .checked_mul((count as u64).into())
+        .map_err(StdError::from)?;
+    if wrapper.amount < required {
+        return Err(ContractError::InsufficientTokens {
+            token: token.to_string(),
+            required,
+        });
+    }
+    let events = draw_pixels(deps.branch(), env, canvas_id, &painter, pixels)?;
+
+    CW20_TREASURY.update(deps.storage, &token, |collected| -> StdResult<_> {
+        Ok(collected.unwrap_or_default().checked_add(required)?)
+    })?;
+
+    let mut response = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("pixels", count.to_string())
+        .add_events(events);
+    let refund = wrapper.amount - required;
+    if !refund.is_zero() {
+        response = response.add_message(cw20_transfer(&token, &painter, refund)?);
+    }
+    Ok(response)
+}
+
+/// A pixel being changed, reported to indexers as an event.
+struct PixelChange {
+    canvas_id: u64,
+    /// Global pixel coordinates
+    x: u64,
+    y: u64,
+    color: u8,
+    painter: Option<Addr>,
+    previous_color: u8,
+    previous_painter: Option<Addr>,
+}
+
+impl PixelChange {
+    /// Painters are left out when there is none, as empty attribute values
+    /// are rejected by the chain.
+    fn into_event(self, ty: &str, chunk_size: u64) -> Event {
+        let mut event = Event::new(ty)
+            .add_attribute("canvas_id", self.canvas_id.to_string())
+            .add_attribute("chunk_x", (self.x / chunk_size).to_string())
+            .add_attribute("chunk_y", (self.y / chunk_size).to_string())
+            .add_attribute("x", (self.x % chunk_size).to_string())
+            .add_attribute("y", (self.y % chunk_size).to_string())
+            .add_attribute("global_x", self.x.to_string())
+            .add_attribute("global_y", self.y.to_string())
+            .add_attribute("color", self.color.to_string());
+        if let Some(painter) = self.painter {
+            event = event.add_attribute("painter", painter);
+        }
+        event = event.add_attribute("previous_color", self.previous_color.to_string());
+        if let Some(previous_painter) = self.previous_painter {
+            event = event.add_attribute("previous_painter", previous_painter);
+        }
+        event
+    }
+}
+
+/// Looks up a painter's address, caching it since a chunk often repeats painters.
+fn painter_address(
+    storage: &dyn Storage,
+    cache: &mut BTreeMap<u32, Addr>,
+    painter_id: Option<u32>,
+) -> StdResult<Option<Addr>> {
+    let painter_id = match painter_id {
+        Some(painter_id) => painter_id,
+        None => return Ok(None),
+    };
+    if let Entry::Vacant(entry) = cache.entry(painter_id) {
+        entry.insert(PAINTER_ADDRESSES.load(storage, painter_id)?);
+    }
+    Ok(Some(cache[&painter_id].clone()))
+}
+
+/// Runs every check a draw has to pass before anything is painted, shared
+/// with the `CanDraw` query so both always agree.
+fn check_draw(
+    storage: &dyn Storage,
+    config: &Config,
+    canvas_id: u64,
+    canvas: &Canvas,
+    painter: &Addr,
+    now: u64,
+    pixels: &[PixelDraw],
+) -> Result<(), ContractError> {
+    let dimensions = &canvas.dimensions;
+    let palette = PALETTE.load(storage)?;
     let user_cooldown = COOLDOWNS
-        .may_load(deps.storage, &info.sender)?
+        .may_load(storage, (canvas_id, painter))?
         .unwrap_or_default();
-    validate_color(color)?;
-    if x > CHUNK_SIZE - 1
-        || y > CHUNK_SIZE - 1
-        || chunk_x > dimensions.width - 1
-        || chunk_y > dimensions.height - 1
-    {
-        return Err(ContractError::InvalidCoordinates {});
+
+    if config.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    if BANNED.has(storage, painter) {
+        return Err(ContractError::Banned {});
+    }
+
+    if config.allowlist_enabled && !ALLOWLIST.has(storage, painter) {
+        return Err(ContractError::NotOnAllowlist {});
+    }
+
+    let locked_regions = LOCKED_REGIONS
+        .prefix(canvas_id)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for pixel in pixels {
+        validate_color(pixel.color, &palette)?;
+        if pixel.x >= dimensions.chunk_size
+            || pixel.y >= dimensions.chunk_size
+            || pixel.chunk_x >= dimensions.width
+            || pixel.chunk_y >= dimensions.height
+        {
+            return Err(ContractError::InvalidCoordinates {});
+        }
+        let x = pixel.chunk_x * dimensions.chunk_size + pixel.x;
+        let y = pixel.chunk_y * dimensions.chunk_size + pixel.y;
+        if let Some((region_id, _)) = locked_regions
+            .iter()
+            .find(|(_, region)| region.contains(x, y))
+        {
+            return Err(ContractError::RegionLocked {
+                region_id: *region_id,
+            });
+        }
     }
 
-    if env.block.height < user_cooldown {
-        return Err(ContractError::StillOnCooldown {});
+    if let Some(start_height) = canvas.start_height {
+        if now < start_height {
+            return Err(ContractError::NotStarted { start_height });
+        }
     }
 
-    if let Some(end_height) = config.end_height {
-        if env.block.height > end_height {
+    if let Some(end_height) = canvas.end_height {
+        if now > end_height {
             return Err(ContractError::EndHeightReached {});
         }
     }
 
-    let default = vec![
-        vec![
-            PixelInfo {
-                color: 0, // White
-                painter: None
+    // Checked after the canvas wide limits so those are reported first
+    if now < user_cooldown {
+        return Err(ContractError::StillOnCooldown {
+            remaining: user_cooldown - now,
+        });
+    }
+
+    Ok(())
+}
+
+/// Validates and paints every pixel for the painter, then charges their cooldown.
+/// Pixels are grouped by chunk so each chunk is only loaded and saved once.
+/// Returns a `draw_pixel` event for every pixel.
+fn draw_pixels(
+    deps: DepsMut,
+    env: Env,
+    canvas_id: u64,
+    painter: &Addr,
+    pixels: Vec<PixelDraw>,
+) -> Result<Vec<Event>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let canvas = load_canvas(deps.storage, canvas_id)?;
+    let dimensions = &canvas.dimensions;
+    let now = config.clock_mode.now(&env.block);
+    check_draw(
+        deps.storage,
+        &config,
+        canvas_id,
+        &canvas,
+        painter,
+        now,
+        &pixels,
+    )?;
+
+    let mut chunks: BTreeMap<(u64, u64), Vec<PixelDraw>> = BTreeMap::new();
+    for pixel in pixels.iter() {
+        chunks
+            .entry((pixel.chunk_x, pixel.chunk_y))
+            .or_default()
+            .push(pixel.clone());
+    }
+
+    let painter_id = intern_painter(deps.storage, painter)?;
+    let mut pixels_gained = 0;
+    let mut pixels_lost: BTreeMap<u32, u64> = BTreeMap::new();
+    let mut addresses = BTreeMap::new();
+    let mut events = Vec::with_capacity(pixels.len());
+    for (key, chunk_pixels) in chunks {
+        let mut chunk = load_chunk(deps.storage, canvas_id, key)?
+            .unwrap_or_else(|| default_chunk(dimensions.chunk_size));
+        for pixel in chunk_pixels {
+            let index = (pixel.y * dimensions.chunk_size + pixel.x) as usize;
+            let global = (
+                canvas_id,
+                pixel.chunk_x * dimensions.chunk_size + pixel.x,
+                pixel.chunk_y * dimensions.chunk_size + pixel.y,
+            );
+            let change = PixelChange {
+                canvas_id,
+                x: global.1,
+                y: global.2,
+                color: pixel.color,
+                painter: Some(painter.clone()),
+                previous_color: chunk.color(index),
+                previous_painter: painter_address(
+                    deps.storage,
+                    &mut addresses,
+                    chunk.painter_id(index),
+                )?,
             };
-            CHUNK_SIZE as usize
-        ];
-        CHUNK_SIZE as usize
-    ];
-    let mut chunk = CHUNKS
-        .may_load(deps.storage, (chunk_x, chunk_y))?
-        .unwrap_or(default);
-    chunk[y as usize][x as usize] = PixelInfo {
-        color,
-        painter: Some(info.sender.clone()),
-    };
+            events.push(change.into_event("draw_pixel", dimensions.chunk_size));
+            match chunk.painter_id(index) {
+                Some(owner) if owner == painter_id => {}
+                owner => {
+                    pixels_gained += 1;
+                    if let Some(owner) = owner {
+                        *pixels_lost.entry(owner).or_default() += 1;
+                        PAINTER_PIXELS.remove(deps.storage, (owner, global));
+                    }
+                    PAINTER_PIXELS.save(deps.storage, (painter_id, global), &())?;
+                }
+            }
+            let displaced = (
+                chunk.color(index),
+                chunk.painter_id(index).unwrap_or_default(),
+            );
+            chunk.paint(index, pixel.color, painter_id);
+            if config.history_depth > 0 {
+                let entry = PixelHistory {
+                    color: pixel.color,
+                    painter_id,
+                    height: env.block.height,
+                };
+                record_history(deps.storage, global, entry, displaced, config.history_depth)?;
+            }
+        }
+        save_chunk(deps.storage, canvas_id, key, &chunk, env.block.height)?;
+    }
 
-    CHUNKS.save(deps.storage, (chunk_x, chunk_y), &chunk)?;
-    COOLDOWNS.save(
+    record_painter_stats(
         deps.storage,
-        &info.sender,
-        &(env.block.height + config.cooldown),
+        canvas_id,
+        painter_id,
+        pixels.len() as u64,
+        pixels_gained,
+        env.block.height,
+    )?;
+    for (owner, lost) in pixels_lost {
+        // Painters from before stats were tracked have nothing to take from
+        if let Some(mut stats) = PAINTER_STATS.may_load(deps.storage, (canvas_id, owner))? {
+            stats.pixels_owned = stats.pixels_owned.saturating_sub(lost);
+            PAINTER_STATS.save(deps.storage, (canvas_id, owner), &stats)?;
+        }
+    }
+
+    let cooldown = match config.batch_cooldown {
+        BatchCooldown::PerPixel => canvas.cooldown * pixels.len() as u64,
+        BatchCooldown::PerBatch => canvas.cooldown,
+    };
+    COOLDOWNS.save(deps.storage, (canvas_id, painter), &(now + cooldown))?;
+
+    Ok(events)
+}
+
+/// Adds a draw to the painter's stats and moves them up the ranking.
+fn record_painter_stats(
+    storage: &mut dyn Storage,
+    canvas_id: u64,
+    painter_id: u32,
+    draws: u64,
+    pixels_gained: u64,
+    height: u64,
+) -> StdResult<()> {
+    let mut stats = match PAINTER_STATS.may_load(storage, (canvas_id, painter_id))? {
+        Some(stats) => {
+            PAINTER_RANKING.remove(storage, (canvas_id, stats.total_draws, painter_id));
+            stats
+        }
+        None => PainterStats {
+            first_draw_height: height,
+            ..PainterStats::default()
+        },
+    };
+    stats.total_draws += draws;
+    stats.pixels_owned += pixels_gained;
+    stats.last_draw_height = height;
+    PAINTER_STATS.save(storage, (canvas_id, painter_id), &stats)?;
+    PAINTER_RANKING.save(storage, (canvas_id, stats.total_draws, painter_id), &())
+}
+
+/// Stores a draw in the pixel's history, dropping anything older than `depth` draws.
+/// `displaced` is the color and painter id the draw replaced, kept for the first
+/// recorded draw since earlier draws may not be in the history.
+fn record_history(
+    storage: &mut dyn Storage,
+    pixel: (u64, u64, u64),
+    entry: PixelHistory,
+    displaced: (u8, u32),
+    depth: u64,
+) -> StdResult<()> {
+    let index = PIXEL_DRAW_COUNT
+        .may_load(storage, pixel)?
+        .unwrap_or_default();
+    if index == 0 {
+        PIXEL_HISTORY_START.save(storage, pixel, &displaced)?;
+    }
+    PIXEL_HISTORY.save(storage, (pixel, index), &entry)?;
+    PIXEL_DRAW_COUNT.save(storage, pixel, &(index + 1))?;
+
+    let oldest_kept = (index + 1).saturating_sub(depth);
+    let stale = PIXEL_HISTORY
+        .prefix(pixel)
+        .keys(
+            storage,
+            None,
+            Some(Bound::exclusive(oldest_kept)),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<_>>>()?;
+    for stale_index in stale {
+        PIXEL_HISTORY.remove(storage, (pixel, stale_index));
+        if stale_index == 0 {
+            PIXEL_HISTORY_START.remove(storage, pixel);
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_create_canvas(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cooldown: u64,
+    start_height: Option<u64>,
+    end_height: Option<u64>,
+    width: u64,
+    height: u64,
+    chunk_size: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+
+    let canvas = new_canvas(
+        config.clock_mode.now(&env.block),
+        cooldown,
+        start_height,
+        end_height,
+        width,
+        height,
+        chunk_size,
     )?;
+    let canvas_id = CANVAS_COUNT.load(deps.storage)?;
+    let event = config_change("canvas", &None, &Some(canvas.clone()))?
+        .add_attribute("canvas_id", canvas_id.to_string());
+    CANVASES.save(deps.storage, canvas_id, &canvas)?;
+    CANVAS_COUNT.save(deps.storage, &(canvas_id + 1))?;
 
-    Ok(Response::new().add_attribute("action", "draw"))
+    Ok(Response::new()
+        .add_attribute("action", "create_canvas")
+        .add_attribute("canvas_id", canvas_id.to_string())
+        .add_event(event))
 }
 
-pub fn execute_update_admin(
+pub fn execute_propose_admin(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     new_admin_address: String,
+    expires: Expiration,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
     if info.sender != config.admin_address {
         return Err(ContractError::Unauthorized {});
     }
 
-    let validated_admin_address = deps.api.addr_validate(&new_admin_address)?;
-    config.admin_address = validated_admin_address;
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::PendingAdminExpired {});
+    }
+
+    let pending_admin = PendingAdmin {
+        address: deps.api.addr_validate(&new_admin_address)?,
+        expires,
+    };
+    let event = config_change(
+        "pending_admin",
+        &PENDING_ADMIN.may_load(deps.storage)?,
+        &Some(pending_admin.clone()),
+    )?;
+    PENDING_ADMIN.save(deps.storage, &pending_admin)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_admin")
+        .add_event(event))
+}
+
+pub fn execute_accept_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending_admin = PENDING_ADMIN
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingAdmin {})?;
+    if info.sender != pending_admin.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if pending_admin.expires.is_expired(&env.block) {
+        return Err(ContractError::PendingAdminExpired {});
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let event = config_change(
+        "admin_address",
+        &config.admin_address,
+        &pending_admin.address,
+    )?;
+    config.admin_address = pending_admin.address;
     CONFIG.save(deps.storage, &config)?;
+    PENDING_ADMIN.remove(deps.storage);
 
-    Ok(Response::new().add_attribute("action", "update_admin"))
+    Ok(Response::new()
+        .add_attribute("action", "accept_admin")
+        .add_event(event))
 }
 
-pub fn execute_update_cooldown(
+pub fn execute_grant_role(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    new_cooldown: u64,
+    address: String,
+    role: Role,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
     if info.sender != config.admin_address {
         return Err(ContractError::Unauthorized {});
     }
 
-    config.cooldown = new_cooldown;
-    CONFIG.save(deps.storage, &config)?;
+    let address = deps.api.addr_validate(&address)?;
+    let old_roles = ROLES.may_load(deps.storage, &address)?.unwrap_or_default();
+    let mut roles = old_roles.clone();
+    if !roles.contains(&role) {
+        roles.push(role);
+    }
+    ROLES.save(deps.storage, &address, &roles)?;
 
-    Ok(Response::new().add_attribute("action", "update_cooldown"))
+    Ok(Response::new()
+        .add_attribute("action", "grant_role")
+        .add_event(config_change("roles", &old_roles, &roles)?.add_attribute("address", address)))
 }
 
-pub fn execute_update_end_height(
+pub fn execute_revoke_role(
     deps: DepsMut,
-    env: Env,
+    _env: Env,
     info: MessageInfo,
-    new_end_height: Option<u64>,
+    address: String,
+    role: Role,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
     if info.sender != config.admin_address {
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(end_height) = new_end_height {
-        if end_height <= env.block.height {
-            return Err(ContractError::InvalidEndHeight {});
-        }
+    let address = deps.api.addr_validate(&address)?;
+    let old_roles = ROLES.may_load(deps.storage, &address)?.unwrap_or_default();
+    let mut roles = old_roles.clone();
+    roles.retain(|held| *held != role);
+    if roles.is_empty() {
+        ROLES.remove(deps.storage, &address);
+    } else {
+        ROLES.save(deps.storage, &address, &roles)?;
     }
 
-    config.end_height = new_end_height;
-    CONFIG.save(deps.storage, &config)?;
-
-    Ok(Response::new().add_attribute("action", "update_end_height"))
+    Ok(Response::new()
+        .add_attribute("action", "revoke_role")
+        .add_event(config_change("roles", &old_roles, &roles)?.add_attribute("address", address)))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetConfig {} => to_binary(&CONFIG.load(deps.storage)?),
-        QueryMsg::GetDimensions {} => to_binary(&DIMENSIONS.load(deps.storage)?),
-        QueryMsg::GetCooldown { address } => query_cooldown(deps, address),
-        QueryMsg::GetChunk { x, y } => to_binary(&ChunkResponse {
-            grid: CHUNKS.may_load(deps.storage, (x, y))?.unwrap_or_else(|| {
-                vec![
-                    vec![
-                        PixelInfo {
-                            color: 0, // White
-                            painter: None
-                        };
-                        CHUNK_SIZE as usize
-                    ];
-                    CHUNK_SIZE as usize
-                ]
-            }),
-        }),
-    }
+/// An event recording a setting changing from `old` to `new`, both JSON encoded.
+fn config_change<T: Serialize>(field: &str, old: &T, new: &T) -> StdResult<Event> {
+    let json = |value: &T| to_vec(value).map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+    Ok(Event::new("config_change")
+        .add_attribute("field", field)
+        .add_attribute("old", json(old)?)
+        .add_attribute("new", json(new)?))
 }
 
-pub fn query_cooldown(deps: Deps, address: String) -> StdResult<Binary> {
-    let address = deps.api.addr_validate(&address).unwrap();
-    let current_cooldown = COOLDOWNS
-        .may_load(deps.storage, &address)?
-        .unwrap_or_default();
-    to_binary(&CooldownResponse { current_cooldown })
+pub fn execute_update_cooldown(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    canvas_id: u64,
+    new_cooldown: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+
+    let mut canvas = load_canvas(deps.storage, canvas_id)?;
+    let event = config_change("cooldown", &canvas.cooldown, &new_cooldown)?
+        .add_attribute("canvas_id", canvas_id.to_string());
+    canvas.cooldown = new_cooldown;
+    CANVASES.save(deps.storage, canvas_id, &canvas)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_cooldown")
+        .add_event(event))
+}
+
+pub fn execute_update_start_height(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    canvas_id: u64,
+    new_start_height: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+    let mut canvas = load_canvas(deps.storage, canvas_id)?;
+
+    validate_start_height(new_start_height, canvas.end_height)?;
+
+    let event = config_change("start_height", &canvas.start_height, &new_start_height)?
+        .add_attribute("canvas_id", canvas_id.to_string());
+    canvas.start_height = new_start_height;
+    CANVASES.save(deps.storage, canvas_id, &canvas)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_start_height")
+        .add_event(event))
+}
+
+pub fn execute_update_end_height(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    canvas_id: u64,
+    new_end_height: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+    let mut canvas = load_canvas(deps.storage, canvas_id)?;
+
+    if let Some(end_height) = new_end_height {
+        if end_height <= config.clock_mode.now(&env.block) {
+            return Err(ContractError::InvalidEndHeight {});
+        }
+    }
+    validate_start_height(canvas.start_height, new_end_height)?;
+
+    let event = config_change("end_height", &canvas.end_height, &new_end_height)?
+        .add_attribute("canvas_id", canvas_id.to_string());
+    canvas.end_height = new_end_height;
+    CANVASES.save(deps.storage, canvas_id, &canvas)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_end_height")
+        .add_event(event))
+}
+
+/// Moves the art and everything stored by position with it, then resizes the
+/// canvas. Whatever ends up outside the new size is dropped.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_resize(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    canvas_id: u64,
+    width: u64,
+    height: u64,
+    offset_x: i64,
+    offset_y: i64,
+    force: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+    let mut canvas = load_canvas(deps.storage, canvas_id)?;
+    let chunk_size = canvas.dimensions.chunk_size;
+    let new_dimensions = Dimensions {
+        width,
+        height,
+        chunk_size,
+    };
+    validate_dimensions(&new_dimensions)?;
+
+    // Where a chunk ends up, or `None` if it falls outside the new size
+    let move_chunk = |(x, y): (u64, u64)| {
+        let x = x as i128 + offset_x as i128;
+        let y = y as i128 + offset_y as i128;
+        if x < 0 || y < 0 || x >= width as i128 || y >= height as i128 {
+            return None;
+        }
+        Some((x as u64, y as u64))
+    };
+    // The same for a pixel in global coordinates
+    let move_pixel = |(x, y): (u64, u64)| {
+        let (chunk_x, chunk_y) = move_chunk((x / chunk_size, y / chunk_size))?;
+        Some((
+            chunk_x * chunk_size + x % chunk_size,
+            chunk_y * chunk_size + y % chunk_size,
+        ))
+    };
+
+    // Only chunks that were drawn on are stored, the rest are blank already.
+    // Without an offset only the chunks outside the new size change
+    let max_chunks = (MAX_RESIZE_PIXELS / (chunk_size * chunk_size)).max(1) as usize;
+    let keys = if offset_x == 0 && offset_y == 0 {
+        chunks_outside(
+            deps.storage,
+            canvas_id,
+            &canvas.dimensions,
+            &new_dimensions,
+            max_chunks + 1,
+        )?
+    } else {
+        stored_chunks(deps.storage, canvas_id, None, None, max_chunks + 1)?
+    };
+    if keys.len() > max_chunks {
+        return Err(ContractError::ResizeTooLarge {
+            max_chunks: max_chunks as u64,
+        });
+    }
+
+    // Check nothing drawn on would be dropped before changing anything
+    let mut changed = vec![];
+    for key in keys {
+        let new_key = move_chunk(key);
+        if new_key == Some(key) {
+            continue;
+        }
+        let chunk = match load_chunk(deps.storage, canvas_id, key)? {
+            Some(chunk) => chunk,
+            None => continue,
+        };
+        let drawn_on = (0..(chunk_size * chunk_size) as usize)
+            .any(|index| chunk.painter_id(index).is_some() || chunk.color(index) != 0);
+        if new_key.is_none() && drawn_on && !force {
+            return Err(ContractError::ChunkNotEmpty { x: key.0, y: key.1 });
+        }
+        changed.push((key, new_key, chunk));
+    }
+
+    // Everything that moves is taken out before anything is written back, so
+    // nothing is overwritten before it has moved itself
+    let mut moved_chunks = vec![];
+    let mut moved_pixels = vec![];
+    let mut moved_history = vec![];
+    let mut pixels_lost: BTreeMap<u32, u64> = BTreeMap::new();
+    let mut addresses = BTreeMap::new();
+    let mut events = vec![];
+    let mut dropped = 0;
+    for (key, new_key, chunk) in changed {
+        // Chunks from 0.1.0 have no height yet, they count as modified now so
+        // they stay listed
+        let last_modified = CHUNK_LAST_MODIFIED
+            .may_load(deps.storage, (canvas_id, key.0, key.1))?
+            .unwrap_or(env.block.height);
+        CHUNKS.remove(deps.storage, (canvas_id, key.0, key.1));
+        CHUNK_LAST_MODIFIED.remove(deps.storage, (canvas_id, key.0, key.1));
+        if canvas_id == 0 {
+            v0_1::CHUNKS.remove(deps.storage, key);
+        }
+
+        for index in 0..(chunk_size * chunk_size) as usize {
+            let x = key.0 * chunk_size + index as u64 % chunk_size;
+            let y = key.1 * chunk_size + index as u64 / chunk_size;
+            let painter_id = chunk.painter_id(index);
+            if new_key.is_none() && (painter_id.is_some() || chunk.color(index) != 0) {
+                let change = PixelChange {
+                    canvas_id,
+                    x,
+                    y,
+                    color: 0,
+                    painter: None,
+                    previous_color: chunk.color(index),
+                    previous_painter: painter_address(deps.storage, &mut addresses, painter_id)?,
+                };
+                events.push(change.into_event("drop_pixel", chunk_size));
+            }
+            // Pixels drawn before 0.2.0 are not indexed
+            if let Some(painter_id) = painter_id {
+                if PAINTER_PIXELS.has(deps.storage, (painter_id, (canvas_id, x, y))) {
+                    PAINTER_PIXELS.remove(deps.storage, (painter_id, (canvas_id, x, y)));
+                    match move_pixel((x, y)) {
+                        Some(moved) => moved_pixels.push((painter_id, moved)),
+                        None => *pixels_lost.entry(painter_id).or_default() += 1,
+                    }
+                }
+            }
+        }
+        for record in take_chunk_history(deps.storage, canvas_id, key, chunk_size)? {
+            if let Some(position) = move_pixel(record.position) {
+                moved_history.push(PixelRecord { position, ..record });
+            }
+        }
+        match new_key {
+            Some(new_key) => moved_chunks.push((new_key, chunk, last_modified)),
+            None => dropped += 1,
+        }
+    }
+
+    for ((x, y), chunk, last_modified) in moved_chunks {
+        CHUNKS.save(deps.storage, (canvas_id, x, y), &chunk)?;
+        CHUNK_LAST_MODIFIED.save(deps.storage, (canvas_id, x, y), &last_modified)?;
+    }
+    for (painter_id, (x, y)) in moved_pixels {
+        PAINTER_PIXELS.save(deps.storage, (painter_id, (canvas_id, x, y)), &())?;
+    }
+    for (painter_id, lost) in pixels_lost {
+        if let Some(mut stats) = PAINTER_STATS.may_load(deps.storage, (canvas_id, painter_id))? {
+            stats.pixels_owned = stats.pixels_owned.saturating_sub(lost);
+            PAINTER_STATS.save(deps.storage, (canvas_id, painter_id), &stats)?;
+        }
+    }
+    for record in moved_history {
+        let pixel = (canvas_id, record.position.0, record.position.1);
+        for (index, entry) in record.entries {
+            PIXEL_HISTORY.save(deps.storage, (pixel, index), &entry)?;
+        }
+        PIXEL_DRAW_COUNT.save(deps.storage, pixel, &record.draw_count)?;
+        if let Some(start) = record.start {
+            PIXEL_HISTORY_START.save(deps.storage, pixel, &start)?;
+        }
+    }
+
+    // Locked regions move with the art and are clipped to the new size
+    let clip = |start: u64, length: u64, offset: i64, limit: u64| {
+        let start = start as i128 + offset as i128 * chunk_size as i128;
+        let end = (start + length as i128).min((limit * chunk_size) as i128);
+        let start = start.max(0);
+        (start < end).then(|| (start as u64, (end - start) as u64))
+    };
+    let regions = LOCKED_REGIONS
+        .prefix(canvas_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (region_id, region) in regions {
+        match (
+            clip(region.x, region.width, offset_x, width),
+            clip(region.y, region.height, offset_y, height),
+        ) {
+            (Some((x, width)), Some((y, height))) => {
+                let region = Region {
+                    x,
+                    y,
+                    width,
+                    height,
+                };
+                LOCKED_REGIONS.save(deps.storage, (canvas_id, region_id), &region)?;
+            }
+            _ => LOCKED_REGIONS.remove(deps.storage, (canvas_id, region_id)),
+        }
+    }
+
+    let event = config_change("dimensions", &canvas.dimensions, &new_dimensions)?
+        .add_attribute("canvas_id", canvas_id.to_string())
+        .add_attribute("offset_x", offset_x.to_string())
+        .add_attribute("offset_y", offset_y.to_string());
+    canvas.dimensions = new_dimensions;
+    CANVASES.save(deps.storage, canvas_id, &canvas)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "resize")
+        .add_attribute("dropped_chunks", dropped.to_string())
+        .add_event(event)
+        .add_events(events))
+}
+
+/// Keys of up to `limit` stored chunks of a canvas from `min` up to `max`,
+/// including chunks still stored the 0.1.0 way.
+fn stored_chunks(
+    storage: &dyn Storage,
+    canvas_id: u64,
+    min: Option<(u64, u64)>,
+    max: Option<(u64, u64)>,
+    limit: usize,
+) -> StdResult<Vec<(u64, u64)>> {
+    let mut keys = CHUNKS
+        .sub_prefix(canvas_id)
+        .keys(
+            storage,
+            min.map(Bound::inclusive),
+            max.map(Bound::exclusive),
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    if canvas_id == 0 {
+        keys.extend(
+            v0_1::CHUNKS
+                .keys(
+                    storage,
+                    min.map(Bound::inclusive),
+                    max.map(Bound::exclusive),
+                    Order::Ascending,
+                )
+                .take(limit - keys.len())
+                .collect::<StdResult<Vec<_>>>()?,
+        );
+    }
+    Ok(keys)
+}
+
+/// Keys of up to `limit` stored chunks that fall outside `new` when the canvas
+/// shrinks in place.
+fn chunks_outside(
+    storage: &dyn Storage,
+    canvas_id: u64,
+    old: &Dimensions,
+    new: &Dimensions,
+    limit: usize,
+) -> StdResult<Vec<(u64, u64)>> {
+    // Chunks are ordered by column, so the columns past the new width are one
+    // range and the rows past the new height one range per column left
+    let mut keys = vec![];
+    if new.width < old.width {
+        keys = stored_chunks(storage, canvas_id, Some((new.width, 0)), None, limit)?;
+    }
+    if new.height < old.height {
+        for x in 0..new.width.min(old.width) {
+            if keys.len() >= limit {
+                break;
+            }
+            keys.extend(stored_chunks(
+                storage,
+                canvas_id,
+                Some((x, new.height)),
+                Some((x + 1, 0)),
+                limit - keys.len(),
+            )?);
+        }
+    }
+    Ok(keys)
+}
+
+/// History of one pixel while a resize moves it.
+struct PixelRecord {
+    position: (u64, u64),
+    draw_count: u64,
+    entries: Vec<(u64, PixelHistory)>,
+    start: Option<(u8, u32)>,
+}
+
+/// Takes out the history of every pixel in a chunk.
+fn take_chunk_history(
+    storage: &mut dyn Storage,
+    canvas_id: u64,
+    (chunk_x, chunk_y): (u64, u64),
+    chunk_size: u64,
+) -> StdResult<Vec<PixelRecord>> {
+    let mut records = vec![];
+    for x in chunk_x * chunk_size..(chunk_x + 1) * chunk_size {
+        // Every pixel with history has a draw count
+        let counts = PIXEL_DRAW_COUNT
+            .prefix((canvas_id, x))
+            .range(
+                storage,
+                Some(Bound::inclusive(chunk_y * chunk_size)),
+                Some(Bound::exclusive((chunk_y + 1) * chunk_size)),
+                Order::Ascending,
+            )
+            .collect::<StdResult<Vec<_>>>()?;
+        for (y, draw_count) in counts {
+            let pixel = (canvas_id, x, y);
+            let entries = PIXEL_HISTORY
+                .prefix(pixel)
+                .range(storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?;
+            let start = PIXEL_HISTORY_START.may_load(storage, pixel)?;
+            for (index, _) in &entries {
+                PIXEL_HISTORY.remove(storage, (pixel, *index));
+            }
+            PIXEL_DRAW_COUNT.remove(storage, pixel);
+            PIXEL_HISTORY_START.remove(storage, pixel);
+            records.push(PixelRecord {
+                position: (x, y),
+                draw_count,
+                entries,
+                start,
+            });
+        }
+    }
+    Ok(records)
+}
+
+pub fn execute_update_batch_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_batch_cooldown: BatchCooldown,
+    new_max_batch_size: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+
+    let events = vec![
+        config_change(
+            "batch_cooldown",
+            &config.batch_cooldown,
+            &new_batch_cooldown,
+        )?,
+        config_change(
+            "max_batch_size",
+            &config.max_batch_size,
+            &new_max_batch_size,
+        )?,
+    ];
+    config.batch_cooldown = new_batch_cooldown;
+    config.max_batch_size = new_max_batch_size;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_batch_config")
+        .add_events(events))
+}
+
+pub fn execute_update_price(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_price: Option<Coin>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let event = config_change("price", &config.price, &new_price)?;
+    config.price = new_price;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_price")
+        .add_event(event))
+}
+
+pub fn execute_update_accepted_cw20(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_accepted_cw20: Vec<AcceptedCw20>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_accepted_cw20 = validate_accepted_cw20(deps.as_ref(), new_accepted_cw20)?;
+    let event = config_change("accepted_cw20", &config.accepted_cw20, &new_accepted_cw20)?;
+    config.accepted_cw20 = new_accepted_cw20;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_accepted_cw20")
+        .add_event(event))
+}
+
+pub fn execute_update_palette(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_palette: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+
+    validate_palette(&new_palette)?;
+    let event = config_change("palette", &PALETTE.load(deps.storage)?, &new_palette)?;
+    PALETTE.save(deps.storage, &new_palette)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_palette")
+        .add_event(event))
+}
+
+pub fn execute_extend_palette(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    colors: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+
+    let old_palette = PALETTE.load(deps.storage)?;
+    let mut palette = old_palette.clone();
+    palette.extend(colors);
+    validate_palette(&palette)?;
+    PALETTE.save(deps.storage, &palette)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "extend_palette")
+        .add_event(config_change("palette", &old_palette, &palette)?))
+}
+
+pub fn execute_update_history_depth(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_history_depth: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+
+    let event = config_change("history_depth", &config.history_depth, &new_history_depth)?;
+    config.history_depth = new_history_depth;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_history_depth")
+        .add_event(event))
+}
+
+pub fn execute_update_allowlist_enabled(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_allowlist_enabled: bool,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+
+    let event = config_change(
+        "allowlist_enabled",
+        &config.allowlist_enabled,
+        &new_allowlist_enabled,
+    )?;
+    config.allowlist_enabled = new_allowlist_enabled;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_allowlist_enabled")
+        .add_event(event))
+}
+
+pub fn execute_update_allowlist(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::ConfigManager)?;
+
+    // One event per address whose membership actually changes
+    let mut events = vec![];
+    for address in add {
+        let address = deps.api.addr_validate(&address)?;
+        if !ALLOWLIST.has(deps.storage, &address) {
+            events.push(
+                config_change("allowlist", &false, &true)?.add_attribute("address", &address),
+            );
+        }
+        ALLOWLIST.save(deps.storage, &address, &())?;
+    }
+    for address in remove {
+        let address = deps.api.addr_validate(&address)?;
+        if ALLOWLIST.has(deps.storage, &address) {
+            events.push(
+                config_change("allowlist", &true, &false)?.add_attribute("address", &address),
+            );
+        }
+        ALLOWLIST.remove(deps.storage, &address);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_allowlist")
+        .add_events(events))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_rollback_region(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    canvas_id: u64,
+    rect: Region,
+    to_height: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::Moderator)?;
+
+    let dimensions = load_canvas(deps.storage, canvas_id)?.dimensions;
+    validate_region(&dimensions, &rect)?;
+
+    let limit = limit
+        .unwrap_or(DEFAULT_ROLLBACK_LIMIT)
+        .min(MAX_ROLLBACK_LIMIT) as u64;
+    let pixel_count = rect.width * rect.height;
+    let start = start_after.map_or(0, |index| index + 1);
+    let end = pixel_count.min(start.saturating_add(limit));
+
+    let mut chunks: BTreeMap<(u64, u64), Vec<(u64, u64)>> = BTreeMap::new();
+    for offset in start..end {
+        let x = rect.x + offset % rect.width;
+        let y = rect.y + offset / rect.width;
+        chunks
+            .entry((x / dimensions.chunk_size, y / dimensions.chunk_size))
+            .or_default()
+            .push((x, y));
+    }
+
+    let mut events = vec![];
+    let mut addresses = BTreeMap::new();
+    let mut owned_changes: BTreeMap<u32, i64> = BTreeMap::new();
+    for (key, chunk_pixels) in chunks {
+        let mut chunk = match load_chunk(deps.storage, canvas_id, key)? {
+            Some(chunk) => chunk,
+            None => continue,
+        };
+        let mut changed = false;
+        for (x, y) in chunk_pixels {
+            let global = (canvas_id, x, y);
+            let (color, painter_id) = match pixel_at_height(deps.storage, global, to_height)? {
+                Some(pixel) => pixel,
+                None => continue,
+            };
+            let index = ((y % dimensions.chunk_size) * dimensions.chunk_size
+                + x % dimensions.chunk_size) as usize;
+            let owner = chunk.painter_id(index).unwrap_or_default();
+            if chunk.color(index) == color && owner == painter_id {
+                continue;
+            }
+            let change = PixelChange {
+                canvas_id,
+                x,
+                y,
+                color,
+                painter: painter_address(
+                    deps.storage,
+                    &mut addresses,
+                    Some(painter_id).filter(|id| *id != 0),
+                )?,
+                previous_color: chunk.color(index),
+                previous_painter: painter_address(
+                    deps.storage,
+                    &mut addresses,
+                    chunk.painter_id(index),
+                )?,
+            };
+            events.push(change.into_event("rollback_pixel", dimensions.chunk_size));
+
+            if owner != painter_id {
+                if owner != 0 {
+                    PAINTER_PIXELS.remove(deps.storage, (owner, global));
+                    *owned_changes.entry(owner).or_default() -= 1;
+                }
+                if painter_id != 0 {
+                    PAINTER_PIXELS.save(deps.storage, (painter_id, global), &())?;
+                    *owned_changes.entry(painter_id).or_default() += 1;
+                }
+            }
+            let displaced = (chunk.color(index), owner);
+            chunk.paint(index, color, painter_id);
+            changed = true;
+            if config.history_depth > 0 {
+                let entry = PixelHistory {
+                    color,
+                    painter_id,
+                    height: env.block.height,
+                };
+                record_history(deps.storage, global, entry, displaced, config.history_depth)?;
+            }
+        }
+        if changed {
+            save_chunk(deps.storage, canvas_id, key, &chunk, env.block.height)?;
+        }
+    }
+
+    for (painter_id, change) in owned_changes {
+        if let Some(mut stats) = PAINTER_STATS.may_load(deps.storage, (canvas_id, painter_id))? {
+            stats.pixels_owned = (stats.pixels_owned as i64 + change).max(0) as u64;
+            PAINTER_STATS.save(deps.storage, (canvas_id, painter_id), &stats)?;
+        }
+    }
+
+    let mut response = Response::new()
+        .add_attribute("action", "rollback_region")
+        .add_attribute("restored", events.len().to_string())
+        .add_attribute("complete", (end == pixel_count).to_string());
+    if end > start {
+        response = response.add_attribute("last_pixel", (end - 1).to_string());
+    }
+
+    Ok(response.add_events(events))
+}
+
+/// The color and painter id of a pixel as it was at `height`, or `None` if the
+/// history doesn't go back that far. Before the first recorded draw the pixel
+/// is taken to be whatever that draw replaced, as draws made with the history
+/// disabled or before 0.2.0 are not in it.
+fn pixel_at_height(
+    storage: &dyn Storage,
+    pixel: (u64, u64, u64),
+    height: u64,
+) -> StdResult<Option<(u8, u32)>> {
+    let mut oldest_index = None;
+    for item in PIXEL_HISTORY
+        .prefix(pixel)
+        .range(storage, None, None, Order::Descending)
+    {
+        let (index, entry) = item?;
+        if entry.height <= height {
+            return Ok(Some((entry.color, entry.painter_id)));
+        }
+        oldest_index = Some(index);
+    }
+
+    match oldest_index {
+        Some(0) => PIXEL_HISTORY_START.may_load(storage, pixel),
+        _ => Ok(None),
+    }
+}
+
+pub fn execute_lock_region(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    canvas_id: u64,
+    region: Region,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::Moderator)?;
+
+    let dimensions = load_canvas(deps.storage, canvas_id)?.dimensions;
+    validate_region(&dimensions, &region)?;
+
+    let region_id = LOCKED_REGION_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let event = config_change("locked_region", &None, &Some(region.clone()))?
+        .add_attribute("canvas_id", canvas_id.to_string())
+        .add_attribute("region_id", region_id.to_string());
+    LOCKED_REGIONS.save(deps.storage, (canvas_id, region_id), &region)?;
+    LOCKED_REGION_COUNT.save(deps.storage, &(region_id + 1))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "lock_region")
+        .add_attribute("region_id", region_id.to_string())
+        .add_event(event))
+}
+
+pub fn execute_unlock_region(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    canvas_id: u64,
+    region_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::Moderator)?;
+
+    let region = LOCKED_REGIONS
+        .may_load(deps.storage, (canvas_id, region_id))?
+        .ok_or(ContractError::LockedRegionNotFound { region_id })?;
+    let event = config_change("locked_region", &Some(region), &None)?
+        .add_attribute("canvas_id", canvas_id.to_string())
+        .add_attribute("region_id", region_id.to_string());
+    LOCKED_REGIONS.remove(deps.storage, (canvas_id, region_id));
+
+    Ok(Response::new()
+        .add_attribute("action", "unlock_region")
+        .add_attribute("region_id", region_id.to_string())
+        .add_event(event))
+}
+
+pub fn execute_ban_painter(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    erase: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::Moderator)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    let event = config_change("banned", &BANNED.has(deps.storage, &address), &true)?
+        .add_attribute("address", &address);
+    BANNED.save(deps.storage, &address, &())?;
+
+    let mut response = Response::new()
+        .add_attribute("action", "ban_painter")
+        .add_attribute("address", address.as_str())
+        .add_event(event);
+    if erase {
+        let (complete, events) = erase_painter_pixels(
+            deps.storage,
+            &address,
+            DEFAULT_ERASE_LIMIT,
+            env.block.height,
+        )?;
+        response = response
+            .add_attribute("erase_complete", complete.to_string())
+            .add_events(events);
+    }
+
+    Ok(response)
+}
+
+pub fn execute_unban_painter(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::Moderator)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    let event = config_change("banned", &BANNED.has(deps.storage, &address), &false)?
+        .add_attribute("address", &address);
+    BANNED.remove(deps.storage, &address);
+
+    Ok(Response::new()
+        .add_attribute("action", "unban_painter")
+        .add_attribute("address", address.as_str())
+        .add_event(event))
+}
+
+pub fn execute_erase_painter(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::Moderator)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    if !BANNED.has(deps.storage, &address) {
+        return Err(ContractError::NotBanned {});
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_ERASE_LIMIT).min(MAX_ERASE_LIMIT);
+    let (complete, events) = erase_painter_pixels(deps.storage, &address, limit, env.block.height)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "erase_painter")
+        .add_attribute("address", address.as_str())
+        .add_attribute("erase_complete", complete.to_string())
+        .add_events(events))
+}
+
+/// Resets up to `limit` pixels owned by the painter to color 0, returning
+/// whether none are left and an `erase_pixel` event for every pixel.
+fn erase_painter_pixels(
+    storage: &mut dyn Storage,
+    painter: &Addr,
+    limit: u32,
+    height: u64,
+) -> Result<(bool, Vec<Event>), ContractError> {
+    let painter_id = match PAINTER_IDS.may_load(storage, painter)? {
+        Some(painter_id) => painter_id,
+        None => return Ok((true, vec![])),
+    };
+
+    let owned = PAINTER_PIXELS
+        .prefix(painter_id)
+        .keys(storage, None, None, Order::Ascending)
+        .take(limit as usize + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let complete = owned.len() <= limit as usize;
+
+    let mut chunks: BTreeMap<(u64, u64, u64), Vec<(u64, u64)>> = BTreeMap::new();
+    let mut canvases: BTreeMap<u64, Canvas> = BTreeMap::new();
+    for (canvas_id, x, y) in owned.into_iter().take(limit as usize) {
+        PAINTER_PIXELS.remove(storage, (painter_id, (canvas_id, x, y)));
+        if let Entry::Vacant(entry) = canvases.entry(canvas_id) {
+            entry.insert(load_canvas(storage, canvas_id)?);
+        }
+        let chunk_size = canvases[&canvas_id].dimensions.chunk_size;
+        chunks
+            .entry((canvas_id, x / chunk_size, y / chunk_size))
+            .or_default()
+            .push((x % chunk_size, y % chunk_size));
+    }
+
+    let history_depth = CONFIG.load(storage)?.history_depth;
+    let mut erased: BTreeMap<u64, u64> = BTreeMap::new();
+    let mut events = vec![];
+    for ((canvas_id, chunk_x, chunk_y), chunk_pixels) in chunks {
+        let chunk_size = canvases[&canvas_id].dimensions.chunk_size;
+        let key = (chunk_x, chunk_y);
+        let mut chunk = match load_chunk(storage, canvas_id, key)? {
+            Some(chunk) => chunk,
+            None => continue,
+        };
+        let mut changed = false;
+        for (x, y) in chunk_pixels {
+            let index = (y * chunk_size + x) as usize;
+            if chunk.painter_id(index) == Some(painter_id) {
+                let (x, y) = (chunk_x * chunk_size + x, chunk_y * chunk_size + y);
+                let change = PixelChange {
+                    canvas_id,
+                    x,
+                    y,
+                    color: 0,
+                    painter: None,
+                    previous_color: chunk.color(index),
+                    previous_painter: Some(painter.clone()),
+                };
+                events.push(change.into_event("erase_pixel", chunk_size));
+                let displaced = (chunk.color(index), painter_id);
+                chunk.paint(index, 0, 0);
+                // Recorded so a rollback past the erase doesn't bring the pixel back
+                if history_depth > 0 {
+                    let entry = PixelHistory {
+                        color: 0,
+                        painter_id: 0,
+                        height,
+                    };
+                    record_history(storage, (canvas_id, x, y), entry, displaced, history_depth)?;
+                }
+                *erased.entry(canvas_id).or_default() += 1;
+                changed = true;
+            }
+        }
+        if changed {
+            save_chunk(storage, canvas_id, key, &chunk, height)?;
+        }
+    }
+
+    for (canvas_id, count) in erased {
+        if let Some(mut stats) = PAINTER_STATS.may_load(storage, (canvas_id, painter_id))? {
+            stats.pixels_owned = stats.pixels_owned.saturating_sub(count);
+            PAINTER_STATS.save(storage, (canvas_id, painter_id), &stats)?;
+        }
+    }
+
+    Ok((complete, events))
+}
+
+pub fn execute_pause(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::Moderator)?;
+
+    let events = vec![
+        config_change("paused", &config.paused, &true)?,
+        config_change("pause_reason", &config.pause_reason, &reason)?,
+    ];
+    config.paused = true;
+    config.pause_reason = reason;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "pause")
+        .add_events(events))
+}
+
+pub fn execute_unpause(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    check_role(deps.storage, &config, &info.sender, Role::Moderator)?;
+
+    let events = vec![
+        config_change("paused", &config.paused, &false)?,
+        config_change("pause_reason", &config.pause_reason, &None)?,
+    ];
+    config.paused = false;
+    config.pause_reason = None;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "unpause")
+        .add_events(events))
+}
+
+pub fn execute_withdraw_treasury(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => config.admin_address,
+    };
+
+    let mut messages = vec![];
+    let balance = deps.querier.query_all_balances(&env.contract.address)?;
+    if !balance.is_empty() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: balance,
+        }));
+    }
+    let collected = CW20_TREASURY
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (token, amount) in collected {
+        CW20_TREASURY.remove(deps.storage, &token);
+        if !amount.is_zero() {
+            messages.push(cw20_transfer(&token, &recipient, amount)?);
+        }
+    }
+    if messages.is_empty() {
+        return Err(ContractError::EmptyTreasury {});
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "withdraw_treasury")
+        .add_attribute("recipient", recipient.to_string())
+        .add_messages(messages))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidContractName {
+            name: stored.contract,
+        });
+    }
+
+    let stored_version = Version::parse(&stored.version)?;
+    let current_version = Version::parse(CONTRACT_VERSION)?;
+    if stored_version > current_version {
+        return Err(ContractError::CannotDowngrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    let applied = run_migrations(deps.storage, &stored_version)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("steps", applied.join(",")))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::ListChunks {
+            canvas_id,
+            start_after,
+            limit,
+        } => query_list_chunks(deps, canvas_id, start_after, limit),
+        QueryMsg::GetConfig {} => to_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::GetPendingAdmin {} => to_binary(&PendingAdminResponse {
+            pending_admin: PENDING_ADMIN.may_load(deps.storage)?,
+        }),
+        QueryMsg::GetRoles { start_after, limit } => query_roles(deps, start_after, limit),
+        QueryMsg::GetCanvas { canvas_id } => to_binary(&CANVASES.load(deps.storage, canvas_id)?),
+        QueryMsg::ListCanvases { start_after, limit } => {
+            query_list_canvases(deps, start_after, limit)
+        }
+        QueryMsg::GetDimensions { canvas_id } => {
+            to_binary(&CANVASES.load(deps.storage, canvas_id)?.dimensions)
+        }
+        QueryMsg::GetCooldown { canvas_id, address } => query_cooldown(deps, canvas_id, address),
+        QueryMsg::CanDraw {
+            canvas_id,
+            address,
+            x,
+            y,
+        } => query_can_draw(deps, env, canvas_id, address, x, y),
+        QueryMsg::GetPalette {} => to_binary(&PaletteResponse {
+            colors: PALETTE.load(deps.storage)?,
+        }),
+        QueryMsg::GetChunk { canvas_id, x, y } => query_chunk(deps, canvas_id, x, y),
+        QueryMsg::GetPixel { canvas_id, x, y } => query_pixel(deps, canvas_id, x, y),
+        QueryMsg::GetRegion {
+            canvas_id,
+            x,
+            y,
+            width,
+            height,
+            include_painters,
+        } => query_region(
+            deps,
+            canvas_id,
+            Region {
+                x,
+                y,
+                width,
+                height,
+            },
+            include_painters,
+        ),
+        QueryMsg::GetPainterStats { canvas_id, address } => {
+            query_painter_stats(deps, canvas_id, address)
+        }
+        QueryMsg::GetLeaderboard {
+            canvas_id,
+            start_after,
+            limit,
+        } => query_leaderboard(deps, canvas_id, start_after, limit),
+        QueryMsg::GetLockedRegions {
+            canvas_id,
+            start_after,
+            limit,
+        } => query_locked_regions(deps, canvas_id, start_after, limit),
+        QueryMsg::GetBanned { start_after, limit } => query_banned(deps, start_after, limit),
+        QueryMsg::GetAllowlist { start_after, limit } => query_allowlist(deps, start_after, limit),
+        QueryMsg::GetPixelHistory {
+            canvas_id,
+            x,
+            y,
+            start_after,
+            limit,
+        } => query_pixel_history(deps, canvas_id, x, y, start_after, limit),
+    }
+}
+
+pub fn query_roles(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let operators = ROLES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, roles) = item?;
+            Ok(OperatorRoles { address, roles })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&RolesResponse { operators })
+}
+
+pub fn query_list_canvases(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let canvases = CANVASES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (canvas_id, canvas) = item?;
+            Ok(CanvasInfo { canvas_id, canvas })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&CanvasesResponse { canvases })
+}
+
+pub fn query_painter_stats(deps: Deps, canvas_id: u64, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let stats = match PAINTER_IDS.may_load(deps.storage, &address)? {
+        Some(painter_id) => PAINTER_STATS
+            .may_load(deps.storage, (canvas_id, painter_id))?
+            .unwrap_or_default(),
+        None => PainterStats::default(),
+    };
+
+    to_binary(&stats)
+}
+
+pub fn query_leaderboard(
+    deps: Deps,
+    canvas_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // Resume below the last painter of the previous page
+    let end = match start_after {
+        Some(address) => {
+            let address = deps.api.addr_validate(&address)?;
+            let painter_id = PAINTER_IDS.load(deps.storage, &address)?;
+            let stats = PAINTER_STATS.load(deps.storage, (canvas_id, painter_id))?;
+            Some(Bound::exclusive((stats.total_draws, painter_id)))
+        }
+        None => None,
+    };
+
+    let painters = PAINTER_RANKING
+        .sub_prefix(canvas_id)
+        .keys(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|key| {
+            let (_, painter_id) = key?;
+            Ok(LeaderboardEntry {
+                address: PAINTER_ADDRESSES.load(deps.storage, painter_id)?,
+                stats: PAINTER_STATS.load(deps.storage, (canvas_id, painter_id))?,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&LeaderboardResponse { painters })
+}
+
+pub fn query_locked_regions(
+    deps: Deps,
+    canvas_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let regions = LOCKED_REGIONS
+        .prefix(canvas_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (region_id, region) = item?;
+            Ok(LockedRegion { region_id, region })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&LockedRegionsResponse { regions })
+}
+
+pub fn query_banned(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let addresses = BANNED
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&BannedResponse { addresses })
+}
+
+pub fn query_allowlist(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let addresses = ALLOWLIST
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&AllowlistResponse { addresses })
+}
+
+pub fn query_list_chunks(
+    deps: Deps,
+    canvas_id: u64,
+    start_after: Option<(u64, u64)>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let mut chunks = CHUNK_LAST_MODIFIED
+        .sub_prefix(canvas_id)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let ((x, y), height) = item?;
+            Ok(ChunkInfo {
+                x,
+                y,
+                last_modified: Some(height),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // Chunks written by 0.1.0 belong to canvas 0 until they are converted,
+    // merge them in so they are listed in the same order
+    if canvas_id == 0 {
+        let legacy = v0_1::CHUNKS
+            .keys(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|key| {
+                let (x, y) = key?;
+                Ok(ChunkInfo {
+                    x,
+                    y,
+                    last_modified: None,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        chunks.extend(legacy);
+        chunks.sort_by_key(|chunk| (chunk.x, chunk.y));
+        chunks.truncate(limit);
+    }
+
+    to_binary(&ChunksResponse { chunks })
+}
+
+pub fn query_chunk(deps: Deps, canvas_id: u64, x: u64, y: u64) -> StdResult<Binary> {
+    // Chunks written by 0.1.0 are only converted once they are drawn on
+    if let Some(grid) = load_legacy_grid(deps.storage, canvas_id, (x, y))? {
+        return to_binary(&ChunkResponse { grid });
+    }
+
+    let chunk_size = CANVASES
+        .load(deps.storage, canvas_id)?
+        .dimensions
+        .chunk_size;
+    let chunk = CHUNKS
+        .may_load(deps.storage, (canvas_id, x, y))?
+        .unwrap_or_else(|| default_chunk(chunk_size));
+    let mut painters: BTreeMap<u32, Addr> = BTreeMap::new();
+    let mut grid = Vec::with_capacity(chunk_size as usize);
+    for y in 0..chunk_size {
+        let mut row = Vec::with_capacity(chunk_size as usize);
+        for x in 0..chunk_size {
+            let index = (y * chunk_size + x) as usize;
+            let painter = match chunk.painter_id(index) {
+                Some(painter_id) => Some(match painters.get(&painter_id) {
+                    Some(painter) => painter.clone(),
+                    None => {
+                        let painter = PAINTER_ADDRESSES.load(deps.storage, painter_id)?;
+                        painters.insert(painter_id, painter.clone());
+                        painter
+                    }
+                }),
+                None => None,
+            };
+            row.push(PixelInfo {
+                color: chunk.color(index),
+                painter,
+            });
+        }
+        grid.push(row);
+    }
+
+    to_binary(&ChunkResponse { grid })
+}
+
+pub fn query_pixel(deps: Deps, canvas_id: u64, x: u64, y: u64) -> StdResult<Binary> {
+    let dimensions = CANVASES.load(deps.storage, canvas_id)?.dimensions;
+    let (chunk_x, chunk_y, local_x, local_y) = split_coordinates(&dimensions, x, y)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let key = (chunk_x, chunk_y);
+
+    // Chunks written by 0.1.0 are read as they are, like in `query_chunk`
+    if let Some(grid) = load_legacy_grid(deps.storage, canvas_id, key)? {
+        return to_binary(&grid[local_y as usize][local_x as usize]);
+    }
+
+    let chunk_size = dimensions.chunk_size;
+    let chunk = CHUNKS
+        .may_load(deps.storage, (canvas_id, key.0, key.1))?
+        .unwrap_or_else(|| default_chunk(chunk_size));
+    let index = (local_y * chunk_size + local_x) as usize;
+    let painter = match chunk.painter_id(index) {
+        Some(painter_id) => Some(PAINTER_ADDRESSES.load(deps.storage, painter_id)?),
+        None => None,
+    };
+
+    to_binary(&PixelInfo {
+        color: chunk.color(index),
+        painter,
+    })
+}
+
+pub fn query_region(
+    deps: Deps,
+    canvas_id: u64,
+    region: Region,
+    include_painters: bool,
+) -> StdResult<Binary> {
+    let dimensions = CANVASES.load(deps.storage, canvas_id)?.dimensions;
+    validate_region(&dimensions, &region).map_err(|err| StdError::generic_err(err.to_string()))?;
+    if region.width * region.height > MAX_REGION_PIXELS {
+        return Err(StdError::generic_err(format!(
+            "Region can't be larger than {} pixels",
+            MAX_REGION_PIXELS
+        )));
+    }
+
+    let chunk_size = dimensions.chunk_size;
+    let pixel_count = (region.width * region.height) as usize;
+    let mut colors = Vec::with_capacity(pixel_count);
+    let mut painters = Vec::with_capacity(if include_painters { pixel_count } else { 0 });
+    // Chunks written by 0.1.0 are read as they are, like in `query_chunk`
+    let mut legacy_grids = BTreeMap::new();
+    let mut chunks = BTreeMap::new();
+    let mut addresses: BTreeMap<u32, Addr> = BTreeMap::new();
+    for y in region.y..region.y + region.height {
+        for x in region.x..region.x + region.width {
+            let key = (x / chunk_size, y / chunk_size);
+            let (local_x, local_y) = (x % chunk_size, y % chunk_size);
+            if let Entry::Vacant(entry) = legacy_grids.entry(key) {
+                entry.insert(load_legacy_grid(deps.storage, canvas_id, key)?);
+            }
+            if let Some(grid) = &legacy_grids[&key] {
+                let pixel = &grid[local_y as usize][local_x as usize];
+                colors.push(pixel.color);
+                if include_painters {
+                    painters.push(pixel.painter.clone());
+                }
+                continue;
+            }
+
+            if let Entry::Vacant(entry) = chunks.entry(key) {
+                entry.insert(
+                    CHUNKS
+                        .may_load(deps.storage, (canvas_id, key.0, key.1))?
+                        .unwrap_or_else(|| default_chunk(chunk_size)),
+                );
+            }
+            let chunk = &chunks[&key];
+            let index = (local_y * chunk_size + local_x) as usize;
+            colors.push(chunk.color(index));
+            if include_painters {
+                let painter = match chunk.painter_id(index) {
+                    Some(painter_id) => {
+                        if let Entry::Vacant(entry) = addresses.entry(painter_id) {
+                            entry.insert(PAINTER_ADDRESSES.load(deps.storage, painter_id)?);
+                        }
+                        Some(addresses[&painter_id].clone())
+                    }
+                    None => None,
+                };
+                painters.push(painter);
+            }
+        }
+    }
+
+    to_binary(&RegionResponse {
+        colors: Binary(colors),
+        painters: if include_painters {
+            Some(painters)
+        } else {
+            None
+        },
+    })
+}
+
+pub fn query_cooldown(deps: Deps, canvas_id: u64, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let current_cooldown = COOLDOWNS
+        .may_load(deps.storage, (canvas_id, &address))?
+        .unwrap_or_default();
+    to_binary(&CooldownResponse { current_cooldown })
+}
+
+pub fn query_can_draw(
+    deps: Deps,
+    env: Env,
+    canvas_id: u64,
+    address: String,
+    x: u64,
+    y: u64,
+) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let canvas = CANVASES.load(deps.storage, canvas_id)?;
+    let reason = can_draw(deps, &env, &config, canvas_id, &canvas, &address, x, y)?;
+
+    to_binary(&CanDrawResponse {
+        can_draw: reason.is_none(),
+        reason,
+    })
+}
+
+/// Why the address couldn't draw the pixel right now, if anything stops it.
+#[allow(clippy::too_many_arguments)]
+fn can_draw(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    canvas_id: u64,
+    canvas: &Canvas,
+    address: &Addr,
+    x: u64,
+    y: u64,
+) -> StdResult<Option<DrawRejection>> {
+    let (chunk_x, chunk_y, x, y) = match split_coordinates(&canvas.dimensions, x, y) {
+        Ok(coordinates) => coordinates,
+        Err(ContractError::PixelOutOfBounds { width, height, .. }) => {
+            return Ok(Some(DrawRejection::OutOfBounds { width, height }))
+        }
+        Err(err) => return Err(StdError::generic_err(err.to_string())),
+    };
+    // Color 0 is always in the palette, any color would pass the same checks
+    let pixel = PixelDraw {
+        chunk_x,
+        chunk_y,
+        x,
+        y,
+        color: 0,
+    };
+    let now = config.clock_mode.now(&env.block);
+    let rejection = match check_draw(
+        deps.storage,
+        config,
+        canvas_id,
+        canvas,
+        address,
+        now,
+        &[pixel],
+    ) {
+        Ok(()) => None,
+        Err(ContractError::Std(err)) => return Err(err),
+        Err(ContractError::Paused {}) => Some(DrawRejection::Paused {
+            reason: config.pause_reason.clone(),
+        }),
+        Err(ContractError::Banned {}) => Some(DrawRejection::Banned {}),
+        Err(ContractError::NotOnAllowlist {}) => Some(DrawRejection::NotOnAllowlist {}),
+        Err(ContractError::RegionLocked { region_id }) => {
+            Some(DrawRejection::RegionLocked { region_id })
+        }
+        Err(ContractError::StillOnCooldown { remaining }) => {
+            Some(DrawRejection::Cooldown { remaining })
+        }
+        Err(ContractError::NotStarted { start_height }) => {
+            Some(DrawRejection::NotStarted { start_height })
+        }
+        Err(ContractError::EndHeightReached {}) => Some(DrawRejection::Ended {}),
+        Err(err) => return Err(StdError::generic_err(err.to_string())),
+    };
+    if rejection.is_some() {
+        return Ok(rejection);
+    }
+
+    // Drawing is paid for with the price in native tokens, or any accepted CW20
+    let price = match &config.price {
+        Some(price) if !price.amount.is_zero() => price,
+        _ => return Ok(None),
+    };
+    let balance = deps.querier.query_balance(address, &price.denom)?;
+    if balance.amount >= price.amount {
+        return Ok(None);
+    }
+    for accepted in &config.accepted_cw20 {
+        let balance: BalanceResponse = deps.querier.query_wasm_smart(
+            &accepted.token,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )?;
+        if balance.balance >= accepted.price {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(DrawRejection::InsufficientFunds {
+        required: price.clone(),
+    }))
+}
+
+pub fn query_pixel_history(
+    deps: Deps,
+    canvas_id: u64,
+    x: u64,
+    y: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let end = start_after.map(Bound::exclusive);
+    let history = PIXEL_HISTORY
+        .prefix((canvas_id, x, y))
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| {
+            let (index, entry) = item?;
+            Ok(PixelHistoryEntry {
+                index,
+                color: entry.color,
+                painter: match entry.painter_id {
+                    0 => None,
+                    painter_id => Some(PAINTER_ADDRESSES.load(deps.storage, painter_id)?),
+                },
+                height: entry.height,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&PixelHistoryResponse { history })
 }
@@ -1,14 +1,40 @@
 #[cfg(test)]
 mod tests {
-    use crate::contract::{execute, instantiate, query};
-    use crate::msg::ExecuteMsg::{Draw, UpdateAdmin, UpdateCooldown, UpdateEndHeight};
-    use crate::msg::{ChunkResponse, CooldownResponse, InstantiateMsg, QueryMsg};
-    use crate::state::{Config, Dimensions, PixelInfo};
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{from_binary, Addr, Deps, Env};
+    use crate::contract::{execute, instantiate, migrate, query};
+    use crate::error::ContractError;
+    use crate::migrations::v0_1;
+    use crate::msg::ExecuteMsg::{
+        AcceptAdmin, BanPainter, CreateCanvas, Draw, DrawAt, DrawBatch, ErasePainter,
+        ExtendPalette, GrantRole, LockRegion, Pause, ProposeAdmin, Receive, Resize, RevokeRole,
+        RollbackRegion, UnbanPainter, UnlockRegion, Unpause, UpdateAcceptedCw20, UpdateAllowlist,
+        UpdateAllowlistEnabled, UpdateBatchConfig, UpdateCooldown, UpdateEndHeight,
+        UpdateHistoryDepth, UpdatePalette, UpdatePrice, UpdateStartHeight, WithdrawTreasury,
+    };
+    use crate::msg::{
+        AcceptedCw20, AllowlistResponse, BannedResponse, CanDrawResponse, CanvasesResponse,
+        ChunkInfo, ChunkResponse, ChunksResponse, CooldownResponse, DrawRejection, InstantiateMsg,
+        LeaderboardResponse, LockedRegion, LockedRegionsResponse, MigrateMsg, OperatorRoles,
+        PaletteResponse, PendingAdminResponse, PixelDraw, PixelHistoryEntry, PixelHistoryResponse,
+        QueryMsg, ReceiveMsg, RegionResponse, RolesResponse,
+    };
+    use crate::state::{
+        BatchCooldown, Canvas, ClockMode, Config, Dimensions, PainterStats, PendingAdmin,
+        PixelInfo, Region, Role, CHUNKS, PAINTER_ADDRESSES, PAINTER_COUNT, PAINTER_IDS,
+    };
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
+    };
+    use cosmwasm_std::{
+        coin, coins, from_binary, to_binary, Addr, BankMsg, Binary, CosmosMsg, Deps, Env, Uint128,
+        WasmMsg,
+    };
+    use cw2::{get_contract_version, set_contract_version};
+    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration};
 
     pub const ADDR1: &str = "addr1";
     pub const ADDR2: &str = "addr2";
+    pub const TOKEN: &str = "token";
+    pub const CONTRACT_NAME: &str = "crates.io:juno-pixel";
 
     fn query_config(deps: Deps, env: Env) -> Config {
         let msg = QueryMsg::GetConfig {};
@@ -16,20 +42,53 @@ mod tests {
         from_binary(&bin).unwrap()
     }
 
+    fn query_canvas(deps: Deps, env: Env, canvas_id: u64) -> Canvas {
+        let msg = QueryMsg::GetCanvas { canvas_id };
+        let bin = query(deps, env, msg).unwrap();
+        from_binary(&bin).unwrap()
+    }
+
     fn query_chunk(deps: Deps, env: Env, x: u64, y: u64) -> ChunkResponse {
-        let msg = QueryMsg::GetChunk { x, y };
+        let msg = QueryMsg::GetChunk { canvas_id: 0, x, y };
         let bin = query(deps, env, msg).unwrap();
         from_binary(&bin).unwrap()
     }
 
     fn query_dimensions(deps: Deps, env: Env) -> Dimensions {
-        let msg = QueryMsg::GetDimensions {};
+        let msg = QueryMsg::GetDimensions { canvas_id: 0 };
+        let bin = query(deps, env, msg).unwrap();
+        from_binary(&bin).unwrap()
+    }
+
+    fn query_pixel_history(
+        deps: Deps,
+        env: Env,
+        x: u64,
+        y: u64,
+        start_after: Option<u64>,
+    ) -> PixelHistoryResponse {
+        let msg = QueryMsg::GetPixelHistory {
+            canvas_id: 0,
+            x,
+            y,
+            start_after,
+            limit: None,
+        };
+        let bin = query(deps, env, msg).unwrap();
+        from_binary(&bin).unwrap()
+    }
+
+    fn query_palette(deps: Deps, env: Env) -> PaletteResponse {
+        let msg = QueryMsg::GetPalette {};
         let bin = query(deps, env, msg).unwrap();
         from_binary(&bin).unwrap()
     }
 
     fn query_cooldown(deps: Deps, env: Env, address: String) -> CooldownResponse {
-        let msg = QueryMsg::GetCooldown { address };
+        let msg = QueryMsg::GetCooldown {
+            canvas_id: 0,
+            address,
+        };
         let bin = query(deps, env, msg).unwrap();
         from_binary(&bin).unwrap()
     }
@@ -45,9 +104,19 @@ mod tests {
         let msg = InstantiateMsg {
             admin_address: ADDR1.to_string(),
             cooldown: 30,
+            start_height: None,
             end_height: Some(current_height - 1),
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
             width: 100,
             height: 100,
+            chunk_size: None,
         };
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
 
@@ -55,9 +124,19 @@ mod tests {
         let msg = InstantiateMsg {
             admin_address: ADDR1.to_string(),
             cooldown: 30,
+            start_height: None,
             end_height: Some(current_height + 1),
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
             width: 100,
             height: 100,
+            chunk_size: None,
         };
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
@@ -65,9 +144,19 @@ mod tests {
         let msg = InstantiateMsg {
             admin_address: ADDR1.to_string(),
             cooldown: 30,
+            start_height: None,
             end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
             width: 100,
             height: 100,
+            chunk_size: None,
         };
         instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -77,7 +166,8 @@ mod tests {
             dimensions,
             Dimensions {
                 width: 100,
-                height: 100
+                height: 100,
+                chunk_size: 32,
             }
         );
         let grid = query_chunk(deps.as_ref(), env, 0, 0);
@@ -97,9 +187,19 @@ mod tests {
         let msg = InstantiateMsg {
             admin_address: ADDR1.to_string(),
             cooldown: 30,
+            start_height: None,
             end_height: Some(end_height),
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
             width: 100,
             height: 100,
+            chunk_size: None,
         };
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
@@ -109,6 +209,7 @@ mod tests {
 
         // Try and draw with invalid color code (outside 0-15 range)
         let msg = Draw {
+            canvas_id: 0,
             chunk_x: 0,
             chunk_y: 0,
             x: 0,
@@ -119,6 +220,7 @@ mod tests {
 
         // Try and draw with invalid dimensions (within the chunk)
         let msg = Draw {
+            canvas_id: 0,
             chunk_x: 0,
             chunk_y: 0,
             x: 32,
@@ -129,6 +231,7 @@ mod tests {
 
         // Try and draw with invalid dimensions (chunk dimensions)
         let msg = Draw {
+            canvas_id: 0,
             chunk_x: 100,
             chunk_y: 100,
             x: 16,
@@ -139,6 +242,7 @@ mod tests {
 
         // Successful draw ADDR1
         let msg = Draw {
+            canvas_id: 0,
             chunk_x: 0,
             chunk_y: 0,
             x: 0,
@@ -148,6 +252,7 @@ mod tests {
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
         // Successful draw ADDR2
         let msg = Draw {
+            canvas_id: 0,
             chunk_x: 0,
             chunk_y: 0,
             x: 1,
@@ -181,6 +286,7 @@ mod tests {
 
         // Try and draw prior to cooldown, will error
         let msg = Draw {
+            canvas_id: 0,
             chunk_x: 0,
             chunk_y: 0,
             x: 0,
@@ -192,6 +298,7 @@ mod tests {
         // Override existing color after cooldown
         env.block.height = start_height + 30;
         let msg = Draw {
+            canvas_id: 0,
             chunk_x: 0,
             chunk_y: 0,
             x: 0,
@@ -216,6 +323,7 @@ mod tests {
         // Try and draw after the end_height
         env.block.height = end_height + 1;
         let msg = Draw {
+            canvas_id: 0,
             chunk_x: 0,
             chunk_y: 0,
             x: 0,
@@ -245,29 +353,96 @@ mod tests {
         let msg = InstantiateMsg {
             admin_address: ADDR1.to_string(),
             cooldown: 30,
+            start_height: None,
             end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
             width: 100,
             height: 100,
+            chunk_size: None,
         };
         instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-        // Try and update admin as ADDR2, should error
-        let msg = UpdateAdmin {
+        // Try and propose an admin as ADDR2, should error
+        let expires = Expiration::AtHeight(env.block.height + 10);
+        let propose = ProposeAdmin {
             new_admin_address: ADDR2.to_string(),
+            expires,
         };
-        execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            propose.clone(),
+        )
+        .unwrap_err();
+
+        // Nothing to accept yet
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            AcceptAdmin {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NoPendingAdmin {}));
 
-        // Query config, it hasn't changed
+        // Propose as ADDR1, config hasn't changed until accepted
+        execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), propose).unwrap();
         let config = query_config(deps.as_ref(), env.clone());
         assert_eq!(config.admin_address, Addr::unchecked(ADDR1));
+        let msg = QueryMsg::GetPendingAdmin {};
+        let res: PendingAdminResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.pending_admin,
+            Some(PendingAdmin {
+                address: Addr::unchecked(ADDR2),
+                expires,
+            })
+        );
 
-        // Update as ADDR1, should succeed
-        let msg = UpdateAdmin {
-            new_admin_address: ADDR2.to_string(),
-        };
-        execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
-        let config = query_config(deps.as_ref(), env);
+        // Only the proposed address can accept
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            AcceptAdmin {},
+        )
+        .unwrap_err();
+
+        // Accepting after expiry, should error
+        let mut expired = env.clone();
+        expired.block.height += 10;
+        let err = execute(
+            deps.as_mut(),
+            expired,
+            mock_info(ADDR2, &[]),
+            AcceptAdmin {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PendingAdminExpired {}));
+
+        // Accept as ADDR2, should succeed
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            AcceptAdmin {},
+        )
+        .unwrap();
+        let config = query_config(deps.as_ref(), env.clone());
         assert_eq!(config.admin_address, Addr::unchecked(ADDR2));
+        let msg = QueryMsg::GetPendingAdmin {};
+        let res: PendingAdminResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.pending_admin, None);
     }
 
     #[test]
@@ -280,23 +455,39 @@ mod tests {
         let msg = InstantiateMsg {
             admin_address: ADDR1.to_string(),
             cooldown: 30,
+            start_height: None,
             end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
             width: 100,
             height: 100,
+            chunk_size: None,
         };
         instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
 
         // Try and update cooldown as ADDR2, should error
-        let msg = UpdateCooldown { new_cooldown: 15 };
+        let msg = UpdateCooldown {
+            canvas_id: 0,
+            new_cooldown: 15,
+        };
         execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
-        let config = query_config(deps.as_ref(), env.clone());
-        assert_eq!(config.cooldown, 30);
+        let canvas = query_canvas(deps.as_ref(), env.clone(), 0);
+        assert_eq!(canvas.cooldown, 30);
 
         // Update as ADDR1, should succeed
-        let msg = UpdateCooldown { new_cooldown: 15 };
+        let msg = UpdateCooldown {
+            canvas_id: 0,
+            new_cooldown: 15,
+        };
         execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
-        let config = query_config(deps.as_ref(), env);
-        assert_eq!(config.cooldown, 15);
+        let canvas = query_canvas(deps.as_ref(), env, 0);
+        assert_eq!(canvas.cooldown, 15);
     }
 
     #[test]
@@ -311,34 +502,2755 @@ mod tests {
         let msg = InstantiateMsg {
             admin_address: ADDR1.to_string(),
             cooldown: 30,
+            start_height: None,
             end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
             width: 100,
             height: 100,
+            chunk_size: None,
         };
         instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
 
         // Try and update end height as ADDR2, should error
         let msg = UpdateEndHeight {
+            canvas_id: 0,
             new_end_height: Some(valid_height),
         };
         execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
-        let config = query_config(deps.as_ref(), env.clone());
-        assert_eq!(config.end_height, None);
+        let canvas = query_canvas(deps.as_ref(), env.clone(), 0);
+        assert_eq!(canvas.end_height, None);
 
         // Update as ADDR1, but invalid height
         let msg = UpdateEndHeight {
+            canvas_id: 0,
             new_end_height: Some(invalid_height),
         };
         execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap_err();
-        let config = query_config(deps.as_ref(), env.clone());
-        assert_eq!(config.end_height, None);
+        let canvas = query_canvas(deps.as_ref(), env.clone(), 0);
+        assert_eq!(canvas.end_height, None);
 
         // Update as ADDR1, valid height
         let msg = UpdateEndHeight {
+            canvas_id: 0,
             new_end_height: Some(valid_height),
         };
         execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+        let canvas = query_canvas(deps.as_ref(), env, 0);
+        assert_eq!(canvas.end_height, Some(valid_height));
+    }
+
+    #[test]
+    fn test_update_start_height() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let start_height = env.block.height + 10;
+        let end_height = env.block.height + 20;
+
+        // Start height after the end height, should error
+        let instantiate_msg = |start_height: Option<u64>| InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height,
+            end_height: Some(end_height),
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 100,
+            height: 100,
+            chunk_size: None,
+        };
+        let msg = instantiate_msg(Some(end_height));
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        let msg = instantiate_msg(Some(start_height));
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Drawing before the start height, should error
+        let draw = Draw {
+            canvas_id: 0,
+            chunk_x: 0,
+            chunk_y: 0,
+            x: 0,
+            y: 0,
+            color: 3,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), draw.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NotStarted { .. }));
+        let mut started = env.clone();
+        started.block.height = start_height;
+        execute(deps.as_mut(), started, info.clone(), draw.clone()).unwrap();
+
+        // Try and update start height as ADDR2, should error
+        let msg = UpdateStartHeight {
+            canvas_id: 0,
+            new_start_height: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
+
+        // Update as ADDR1, but after the end height
+        let msg = UpdateStartHeight {
+            canvas_id: 0,
+            new_start_height: Some(end_height + 1),
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+        // Moving the end height before the start height, should error
+        let msg = UpdateEndHeight {
+            canvas_id: 0,
+            new_end_height: Some(start_height - 1),
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+        // Remove the start height, can draw straight away
+        let msg = UpdateStartHeight {
+            canvas_id: 0,
+            new_start_height: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let canvas = query_canvas(deps.as_ref(), env.clone(), 0);
+        assert_eq!(canvas.start_height, None);
+        execute(deps.as_mut(), env, mock_info(ADDR2, &[]), draw).unwrap();
+    }
+
+    #[test]
+    fn test_draw_batch() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let start_height = env.block.height;
+
+        // Instantiate with ADDR1 as admin
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 10,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 3,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 100,
+            height: 100,
+            chunk_size: None,
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let pixel = |chunk_x: u64, x: u64, color: u8| PixelDraw {
+            chunk_x,
+            chunk_y: 0,
+            x,
+            y: 0,
+            color,
+        };
+
+        // Empty batch, should error
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![],
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+        // Batch larger than the max size, should error
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![
+                pixel(0, 0, 3),
+                pixel(0, 1, 3),
+                pixel(0, 2, 3),
+                pixel(0, 3, 3),
+            ],
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+        // Batch with one invalid pixel, nothing is drawn
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![pixel(0, 0, 3), pixel(0, 1, 16)],
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        let grid = query_chunk(deps.as_ref(), env.clone(), 0, 0);
+        assert_eq!(grid.grid[0][0].painter, None);
+
+        // Batch spanning two chunks
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![pixel(0, 0, 3), pixel(1, 5, 5), pixel(0, 1, 10)],
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let grid = query_chunk(deps.as_ref(), env.clone(), 0, 0);
+        assert_eq!(grid.grid[0][0].color, 3);
+        assert_eq!(grid.grid[0][1].color, 10);
+        let grid = query_chunk(deps.as_ref(), env.clone(), 1, 0);
+        assert_eq!(
+            grid.grid[0][5],
+            PixelInfo {
+                color: 5, // Red
+                painter: Some(Addr::unchecked(ADDR1))
+            }
+        );
+
+        // Cooldown charged per pixel
+        let cooldown = query_cooldown(deps.as_ref(), env.clone(), ADDR1.to_string());
+        assert_eq!(cooldown.current_cooldown, start_height + 30);
+
+        // Only the admin can update the batch config
+        let msg = UpdateBatchConfig {
+            new_batch_cooldown: BatchCooldown::PerBatch,
+            new_max_batch_size: 5,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let config = query_config(deps.as_ref(), env.clone());
+        assert_eq!(config.batch_cooldown, BatchCooldown::PerBatch);
+        assert_eq!(config.max_batch_size, 5);
+
+        // Cooldown charged once per batch
+        env.block.height = start_height + 30;
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![
+                pixel(0, 0, 1),
+                pixel(0, 1, 1),
+                pixel(0, 2, 1),
+                pixel(0, 3, 1),
+            ],
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let cooldown = query_cooldown(deps.as_ref(), env, ADDR1.to_string());
+        assert_eq!(cooldown.current_cooldown, start_height + 30 + 10);
+    }
+
+    #[test]
+    fn test_paid_draw() {
+        let mut deps = mock_dependencies_with_balance(&coins(500, "ujuno"));
+        let mut env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+
+        // Instantiate with ADDR1 as admin and a price of 100ujuno per pixel
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: Some(coin(100, "ujuno")),
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 100,
+            height: 100,
+            chunk_size: None,
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let draw = Draw {
+            canvas_id: 0,
+            chunk_x: 0,
+            chunk_y: 0,
+            x: 0,
+            y: 0,
+            color: 3, // Black
+        };
+
+        // No funds, should error
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            draw.clone(),
+        )
+        .unwrap_err();
+
+        // Wrong denom, should error
+        let info2 = mock_info(ADDR2, &coins(100, "uatom"));
+        execute(deps.as_mut(), env.clone(), info2, draw.clone()).unwrap_err();
+
+        // Too little, should error
+        let info2 = mock_info(ADDR2, &coins(99, "ujuno"));
+        execute(deps.as_mut(), env.clone(), info2, draw.clone()).unwrap_err();
+
+        // Exact amount, no refund
+        let info2 = mock_info(ADDR2, &coins(100, "ujuno"));
+        let res = execute(deps.as_mut(), env.clone(), info2, draw.clone()).unwrap();
+        assert!(res.messages.is_empty());
+
+        // Overpayment for a batch is refunded
+        env.block.height += 1;
+        let info2 = mock_info(ADDR2, &coins(250, "ujuno"));
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![
+                PixelDraw {
+                    chunk_x: 0,
+                    chunk_y: 0,
+                    x: 1,
+                    y: 0,
+                    color: 3,
+                },
+                PixelDraw {
+                    chunk_x: 0,
+                    chunk_y: 0,
+                    x: 2,
+                    y: 0,
+                    color: 3,
+                },
+            ],
+        };
+        let res = execute(deps.as_mut(), env.clone(), info2, msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: ADDR2.to_string(),
+                amount: coins(50, "ujuno"),
+            })
+        );
+
+        // Remove the price, drawing is free again
+        let msg = UpdatePrice { new_price: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        env.block.height += 1;
+        execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), draw).unwrap();
+
+        // Only the admin can withdraw, defaults to sending to the admin
+        let msg = WithdrawTreasury { recipient: None };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: ADDR1.to_string(),
+                amount: coins(500, "ujuno"),
+            })
+        );
+
+        // Withdraw to another recipient
+        let msg = WithdrawTreasury {
+            recipient: Some(ADDR2.to_string()),
+        };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: ADDR2.to_string(),
+                amount: coins(500, "ujuno"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_cw20_draw() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let start_height = env.block.height;
+
+        // Instantiate with ADDR1 as admin, accepting 10 TOKEN per pixel
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 30,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![AcceptedCw20 {
+                token: TOKEN.to_string(),
+                price: Uint128::new(10),
+            }],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 100,
+            height: 100,
+            chunk_size: None,
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let receive = |amount: u128, msg: ReceiveMsg| {
+            Receive(Cw20ReceiveMsg {
+                sender: ADDR2.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&msg).unwrap(),
+            })
+        };
+        let draw = ReceiveMsg::Draw {
+            canvas_id: 0,
+            chunk_x: 0,
+            chunk_y: 0,
+            x: 0,
+            y: 0,
+            color: 3, // Black
+        };
+
+        // Token not accepted, should error
+        let msg = receive(10, draw.clone());
+        execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap_err();
+
+        // Too few tokens, should error
+        let msg = receive(9, draw.clone());
+        execute(deps.as_mut(), env.clone(), mock_info(TOKEN, &[]), msg).unwrap_err();
+
+        // Invalid coordinates are still rejected
+        let msg = receive(
+            10,
+            ReceiveMsg::Draw {
+                canvas_id: 0,
+                chunk_x: 0,
+                chunk_y: 0,
+                x: 32,
+                y: 0,
+                color: 3,
+            },
+        );
+        execute(deps.as_mut(), env.clone(), mock_info(TOKEN, &[]), msg).unwrap_err();
+
+        // Exact amount, pixel is drawn by the token sender
+        let msg = receive(10, draw.clone());
+        let res = execute(deps.as_mut(), env.clone(), mock_info(TOKEN, &[]), msg).unwrap();
+        assert!(res.messages.is_empty());
+        let grid = query_chunk(deps.as_ref(), env.clone(), 0, 0);
+        assert_eq!(grid.grid[0][0].painter, Some(Addr::unchecked(ADDR2)));
+        let cooldown = query_cooldown(deps.as_ref(), env.clone(), ADDR2.to_string());
+        assert_eq!(cooldown.current_cooldown, start_height + 30);
+
+        // Still on cooldown, should error
+        let msg = receive(10, draw);
+        execute(deps.as_mut(), env.clone(), mock_info(TOKEN, &[]), msg).unwrap_err();
+
+        // Batch draw with overpayment, extra tokens are refunded
+        env.block.height = start_height + 30;
+        let msg = receive(
+            25,
+            ReceiveMsg::DrawBatch {
+                canvas_id: 0,
+                pixels: vec![
+                    PixelDraw {
+                        chunk_x: 0,
+                        chunk_y: 0,
+                        x: 1,
+                        y: 0,
+                        color: 5,
+                    },
+                    PixelDraw {
+                        chunk_x: 0,
+                        chunk_y: 0,
+                        x: 2,
+                        y: 0,
+                        color: 5,
+                    },
+                ],
+            },
+        );
+        let res = execute(deps.as_mut(), env.clone(), mock_info(TOKEN, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: ADDR2.to_string(),
+                    amount: Uint128::new(5),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        // Withdraw sends the collected tokens to the admin
+        let msg = WithdrawTreasury { recipient: None };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: ADDR1.to_string(),
+                    amount: Uint128::new(30),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+        // Nothing left to withdraw
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+        // Only the admin can update the accepted tokens
+        let msg = UpdateAcceptedCw20 {
+            new_accepted_cw20: vec![],
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
         let config = query_config(deps.as_ref(), env);
-        assert_eq!(config.end_height, Some(valid_height));
+        assert!(config.accepted_cw20.is_empty());
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        // Write state as a 0.1.0 contract would have left it
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let old_config = v0_1::Config {
+            admin_address: Addr::unchecked(ADDR1),
+            cooldown: 30,
+            end_height: Some(env.block.height + 100),
+        };
+        v0_1::CONFIG
+            .save(deps.as_mut().storage, &old_config)
+            .unwrap();
+        let old_dimensions = v0_1::Dimensions {
+            width: 10,
+            height: 20,
+        };
+        v0_1::DIMENSIONS
+            .save(deps.as_mut().storage, &old_dimensions)
+            .unwrap();
+
+        // Migrate, config is rewritten in the new format
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        let config = query_config(deps.as_ref(), env.clone());
+        assert_eq!(
+            config,
+            Config {
+                admin_address: Addr::unchecked(ADDR1),
+                batch_cooldown: BatchCooldown::PerPixel,
+                max_batch_size: 1,
+                price: None,
+                accepted_cw20: vec![],
+                history_depth: 0,
+                allowlist_enabled: false,
+                clock_mode: ClockMode::Height,
+                paused: false,
+                pause_reason: None,
+            }
+        );
+        // The existing canvas becomes canvas 0
+        let canvas = query_canvas(deps.as_ref(), env.clone(), 0);
+        assert_eq!(
+            canvas,
+            Canvas {
+                dimensions: Dimensions {
+                    width: 10,
+                    height: 20,
+                    chunk_size: 32,
+                },
+                cooldown: 30,
+                start_height: None,
+                end_height: Some(env.block.height + 100),
+            }
+        );
+        let palette = query_palette(deps.as_ref(), env.clone());
+        assert_eq!(palette.colors.len(), 16);
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+        // Migrating again to the same version is a no-op
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        assert_eq!(query_config(deps.as_ref(), env.clone()), config);
+
+        // Cannot downgrade
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap_err();
+
+        // Cannot migrate from a different contract
+        set_contract_version(deps.as_mut().storage, "crates.io:other", "0.1.0").unwrap();
+        migrate(deps.as_mut(), env, MigrateMsg {}).unwrap_err();
+    }
+
+    #[test]
+    fn test_legacy_chunk_conversion() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 100,
+            height: 100,
+            chunk_size: None,
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Write a chunk in the nested JSON format used by 0.1.0
+        let mut grid = vec![
+            vec![
+                PixelInfo {
+                    color: 0,
+                    painter: None
+                };
+                32
+            ];
+            32
+        ];
+        grid[4][2] = PixelInfo {
+            color: 5, // Red
+            painter: Some(Addr::unchecked(ADDR2)),
+        };
+        v0_1::CHUNKS
+            .save(deps.as_mut().storage, (0, 0), &grid)
+            .unwrap();
+
+        // Queries read the legacy chunk without converting it
+        let chunk = query_chunk(deps.as_ref(), env.clone(), 0, 0);
+        assert_eq!(chunk.grid, grid);
+        assert!(CHUNKS
+            .may_load(deps.as_ref().storage, (0, 0, 0))
+            .unwrap()
+            .is_none());
+
+        // Drawing converts the chunk, keeping the existing pixels
+        let msg = Draw {
+            canvas_id: 0,
+            chunk_x: 0,
+            chunk_y: 0,
+            x: 3,
+            y: 4,
+            color: 3, // Black
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert!(v0_1::CHUNKS
+            .may_load(deps.as_ref().storage, (0, 0))
+            .unwrap()
+            .is_none());
+        let packed = CHUNKS.load(deps.as_ref().storage, (0, 0, 0)).unwrap();
+        assert_eq!(packed.colors.len(), 32 * 32);
+        // Painters are stored as compact ids, assigned in order of first draw
+        assert_eq!(packed.painter_id(4 * 32 + 3), Some(1));
+        assert_eq!(packed.painter_id(4 * 32 + 2), Some(2));
+        assert_eq!(packed.painter_id(0), None);
+        let addr1 = Addr::unchecked(ADDR1);
+        let addr2 = Addr::unchecked(ADDR2);
+        assert_eq!(PAINTER_IDS.load(deps.as_ref().storage, &addr1).unwrap(), 1);
+        assert_eq!(PAINTER_IDS.load(deps.as_ref().storage, &addr2).unwrap(), 2);
+        assert_eq!(
+            PAINTER_ADDRESSES.load(deps.as_ref().storage, 2).unwrap(),
+            addr2
+        );
+
+        grid[4][3] = PixelInfo {
+            color: 3,
+            painter: Some(Addr::unchecked(ADDR1)),
+        };
+        let chunk = query_chunk(deps.as_ref(), env.clone(), 0, 0);
+        assert_eq!(chunk.grid, grid);
+
+        // Drawing again reuses the painter's id
+        let msg = Draw {
+            canvas_id: 0,
+            chunk_x: 1,
+            chunk_y: 0,
+            x: 0,
+            y: 0,
+            color: 3, // Black
+        };
+        execute(deps.as_mut(), env, info, msg).unwrap();
+        let packed = CHUNKS.load(deps.as_ref().storage, (0, 1, 0)).unwrap();
+        assert_eq!(packed.painter_id(0), Some(1));
+        assert_eq!(PAINTER_COUNT.load(deps.as_ref().storage).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_pixel_history() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let start_height = env.block.height;
+
+        // Instantiate keeping the last 3 draws of each pixel
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 3,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 100,
+            height: 100,
+            chunk_size: None,
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Draw the same pixel 4 times, on chunk (1, 2) at (3, 4)
+        for color in 1..=4 {
+            env.block.height = start_height + color as u64;
+            let sender = if color % 2 == 0 { ADDR2 } else { ADDR1 };
+            let msg = Draw {
+                canvas_id: 0,
+                chunk_x: 1,
+                chunk_y: 2,
+                x: 3,
+                y: 4,
+                color,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg).unwrap();
+        }
+
+        // History is keyed by global coordinates, newest first, oldest draw dropped
+        let history = query_pixel_history(deps.as_ref(), env.clone(), 35, 68, None);
+        assert_eq!(
+            history.history,
+            vec![
+                PixelHistoryEntry {
+                    index: 3,
+                    color: 4,
+                    painter: Some(Addr::unchecked(ADDR2)),
+                    height: start_height + 4,
+                },
+                PixelHistoryEntry {
+                    index: 2,
+                    color: 3,
+                    painter: Some(Addr::unchecked(ADDR1)),
+                    height: start_height + 3,
+                },
+                PixelHistoryEntry {
+                    index: 1,
+                    color: 2,
+                    painter: Some(Addr::unchecked(ADDR2)),
+                    height: start_height + 2,
+                },
+            ]
+        );
+
+        // Paginate from an index
+        let history = query_pixel_history(deps.as_ref(), env.clone(), 35, 68, Some(2));
+        assert_eq!(history.history.len(), 1);
+        assert_eq!(history.history[0].index, 1);
+
+        // Untouched pixels have no history
+        let history = query_pixel_history(deps.as_ref(), env.clone(), 0, 0, None);
+        assert!(history.history.is_empty());
+
+        // Lowering the depth trims history on the next draw
+        let msg = UpdateHistoryDepth {
+            new_history_depth: 1,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = Draw {
+            canvas_id: 0,
+            chunk_x: 1,
+            chunk_y: 2,
+            x: 3,
+            y: 4,
+            color: 5,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let history = query_pixel_history(deps.as_ref(), env, 35, 68, None);
+        assert_eq!(history.history.len(), 1);
+        assert_eq!(history.history[0].index, 4);
+        assert_eq!(history.history[0].color, 5);
+    }
+
+    #[test]
+    fn test_palette() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let palette: Vec<String> = (0..32).map(|i| format!("#0000{:02X}", i)).collect();
+
+        let instantiate_msg = |palette: Option<Vec<String>>| InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette,
+            width: 100,
+            height: 100,
+            chunk_size: None,
+        };
+
+        // Invalid palettes, should error
+        for invalid in [
+            vec![],
+            vec!["#FFF".to_string()],
+            vec!["#GGGGGG".to_string()],
+        ] {
+            let msg = instantiate_msg(Some(invalid));
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        }
+        let msg = instantiate_msg(Some(vec!["#FFFFFF".to_string(); 257]));
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+        // Default palette has 16 colors
+        let msg = instantiate_msg(None);
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let response = query_palette(deps.as_ref(), env.clone());
+        assert_eq!(response.colors.len(), 16);
+        assert_eq!(response.colors[0], "#FFFFFF");
+
+        // Instantiate with a 32 color palette
+        let msg = instantiate_msg(Some(palette.clone()));
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let response = query_palette(deps.as_ref(), env.clone());
+        assert_eq!(response.colors, palette);
+
+        let draw = |color: u8| Draw {
+            canvas_id: 0,
+            chunk_x: 0,
+            chunk_y: 0,
+            x: 0,
+            y: 0,
+            color,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(31)).unwrap();
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(32)).unwrap_err();
+
+        // Extend the palette, only as admin
+        let msg = ExtendPalette {
+            colors: vec!["#ABCDEF".to_string()],
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let response = query_palette(deps.as_ref(), env.clone());
+        assert_eq!(response.colors.len(), 33);
+        assert_eq!(response.colors[32], "#ABCDEF");
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(32)).unwrap();
+
+        // Extending past 256 colors, should error
+        let msg = ExtendPalette {
+            colors: vec!["#000000".to_string(); 224],
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+        // Replace the palette, only as admin
+        let msg = UpdatePalette {
+            new_palette: vec!["#000000".to_string(), "#FFFFFF".to_string()],
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let response = query_palette(deps.as_ref(), env.clone());
+        assert_eq!(response.colors.len(), 2);
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(1)).unwrap();
+        execute(deps.as_mut(), env, info, draw(2)).unwrap_err();
+    }
+
+    #[test]
+    fn test_chunk_size() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+
+        let instantiate_msg = |chunk_size: Option<u64>| InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 1,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 10,
+            height: 10,
+            chunk_size,
+        };
+
+        // Invalid chunk sizes, should error
+        let msg = instantiate_msg(Some(0));
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        let msg = instantiate_msg(Some(129));
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+        // Empty canvases and ones too large to address, should error
+        for (width, height) in [(0, 10), (10, 0), (u64::MAX, 10), (1 << 40, 1 << 40)] {
+            let msg = InstantiateMsg {
+                width,
+                height,
+                ..instantiate_msg(Some(8))
+            };
+            let err = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidDimensions {}));
+        }
+
+        // Instantiate with 8x8 chunks
+        let msg = instantiate_msg(Some(8));
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let dimensions = query_dimensions(deps.as_ref(), env.clone());
+        assert_eq!(dimensions.chunk_size, 8);
+        let grid = query_chunk(deps.as_ref(), env.clone(), 0, 0);
+        assert_eq!(grid.grid.len(), 8);
+        assert_eq!(grid.grid[0].len(), 8);
+
+        // Outside the chunk, should error
+        let msg = Draw {
+            canvas_id: 0,
+            chunk_x: 0,
+            chunk_y: 0,
+            x: 8,
+            y: 0,
+            color: 3,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+        // Last pixel of the chunk
+        let msg = Draw {
+            canvas_id: 0,
+            chunk_x: 1,
+            chunk_y: 0,
+            x: 7,
+            y: 7,
+            color: 3,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let grid = query_chunk(deps.as_ref(), env.clone(), 1, 0);
+        assert_eq!(grid.grid[7][7].color, 3);
+
+        // Global coordinates use the chunk size
+        let history = query_pixel_history(deps.as_ref(), env, 15, 7, None);
+        assert_eq!(history.history.len(), 1);
+    }
+
+    #[test]
+    fn test_multiple_canvases() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 30,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 10,
+            height: 10,
+            chunk_size: None,
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Create a canvas as ADDR2, should error
+        let create = CreateCanvas {
+            cooldown: 5,
+            start_height: None,
+            end_height: None,
+            width: 2,
+            height: 2,
+            chunk_size: Some(8),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            create.clone(),
+        )
+        .unwrap_err();
+
+        // Create as ADDR1, should get the next id
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), create).unwrap();
+        assert_eq!(res.attributes[1].value, "1");
+        let canvas = query_canvas(deps.as_ref(), env.clone(), 1);
+        assert_eq!(canvas.cooldown, 5);
+        assert_eq!(canvas.dimensions.chunk_size, 8);
+
+        // Drawing on a canvas that does not exist, should error
+        let draw = |canvas_id: u64| Draw {
+            canvas_id,
+            chunk_x: 1,
+            chunk_y: 1,
+            x: 3,
+            y: 3,
+            color: 3,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(2)).unwrap_err();
+
+        // Chunks and cooldowns are separate per canvas
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(0)).unwrap();
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(0)).unwrap_err();
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(1)).unwrap();
+        let msg = QueryMsg::GetChunk {
+            canvas_id: 1,
+            x: 1,
+            y: 1,
+        };
+        let chunk: ChunkResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(chunk.grid.len(), 8);
+        assert_eq!(chunk.grid[3][3].color, 3);
+        let chunk = query_chunk(deps.as_ref(), env.clone(), 1, 1);
+        assert_eq!(chunk.grid.len(), 32);
+        assert_eq!(chunk.grid[3][3].color, 3);
+        let msg = QueryMsg::GetCooldown {
+            canvas_id: 1,
+            address: ADDR1.to_string(),
+        };
+        let cooldown: CooldownResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(cooldown.current_cooldown, env.block.height + 5);
+
+        // List canvases, paginated by id
+        let msg = QueryMsg::ListCanvases {
+            start_after: None,
+            limit: None,
+        };
+        let res: CanvasesResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let ids: Vec<u64> = res.canvases.iter().map(|c| c.canvas_id).collect();
+        assert_eq!(ids, vec![0, 1]);
+        let msg = QueryMsg::ListCanvases {
+            start_after: Some(0),
+            limit: None,
+        };
+        let res: CanvasesResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.canvases.len(), 1);
+        assert_eq!(res.canvases[0].canvas, canvas);
+    }
+
+    #[test]
+    fn test_leaderboard() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 10,
+            height: 10,
+            chunk_size: None,
+        };
+        instantiate(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+
+        let pixel = |x: u64| PixelDraw {
+            chunk_x: 0,
+            chunk_y: 0,
+            x,
+            y: 0,
+            color: 3,
+        };
+        let query_stats = |deps: Deps, address: &str| -> PainterStats {
+            let msg = QueryMsg::GetPainterStats {
+                canvas_id: 0,
+                address: address.to_string(),
+            };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let query_leaderboard = |deps: Deps, start_after: Option<String>| {
+            let msg = QueryMsg::GetLeaderboard {
+                canvas_id: 0,
+                start_after,
+                limit: Some(1),
+            };
+            let res: LeaderboardResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.painters
+        };
+
+        // Nobody has drawn yet
+        assert_eq!(query_stats(deps.as_ref(), ADDR1), PainterStats::default());
+        assert!(query_leaderboard(deps.as_ref(), None).is_empty());
+
+        // ADDR1 draws 3 pixels, drawing over one of them twice
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![pixel(0), pixel(1), pixel(1)],
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+
+        // ADDR2 takes over one of them a block later
+        let mut later = env.clone();
+        later.block.height += 1;
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![pixel(1)],
+        };
+        execute(deps.as_mut(), later.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+
+        assert_eq!(
+            query_stats(deps.as_ref(), ADDR1),
+            PainterStats {
+                total_draws: 3,
+                pixels_owned: 1,
+                first_draw_height: env.block.height,
+                last_draw_height: env.block.height,
+            }
+        );
+        assert_eq!(
+            query_stats(deps.as_ref(), ADDR2),
+            PainterStats {
+                total_draws: 1,
+                pixels_owned: 1,
+                first_draw_height: later.block.height,
+                last_draw_height: later.block.height,
+            }
+        );
+
+        // Most draws first, paginated by address
+        let page = query_leaderboard(deps.as_ref(), None);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].address, Addr::unchecked(ADDR1));
+        let page = query_leaderboard(deps.as_ref(), Some(ADDR1.to_string()));
+        assert_eq!(page[0].address, Addr::unchecked(ADDR2));
+        let page = query_leaderboard(deps.as_ref(), Some(ADDR2.to_string()));
+        assert!(page.is_empty());
+
+        // ADDR2 overtakes ADDR1
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![pixel(2), pixel(3), pixel(4)],
+        };
+        execute(deps.as_mut(), later, mock_info(ADDR2, &[]), msg).unwrap();
+        let page = query_leaderboard(deps.as_ref(), None);
+        assert_eq!(page[0].address, Addr::unchecked(ADDR2));
+        assert_eq!(page[0].stats.total_draws, 4);
+        assert_eq!(page[0].stats.pixels_owned, 4);
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: true,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 10,
+            height: 10,
+            chunk_size: None,
+        };
+        instantiate(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+
+        let draw = Draw {
+            canvas_id: 0,
+            chunk_x: 0,
+            chunk_y: 0,
+            x: 0,
+            y: 0,
+            color: 3,
+        };
+        let query_allowlist = |deps: Deps, start_after: Option<String>| {
+            let msg = QueryMsg::GetAllowlist {
+                start_after,
+                limit: Some(1),
+            };
+            let res: AllowlistResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.addresses
+        };
+
+        // Not on the allowlist, should error
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            draw.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotOnAllowlist {}));
+
+        // Update the allowlist as ADDR2, should error
+        let update = UpdateAllowlist {
+            add: vec![ADDR1.to_string(), ADDR2.to_string()],
+            remove: vec![],
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            update.clone(),
+        )
+        .unwrap_err();
+
+        // Add both as ADDR1, ADDR2 can now draw
+        execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), update).unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            draw.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            query_allowlist(deps.as_ref(), None),
+            vec![Addr::unchecked(ADDR1)]
+        );
+        assert_eq!(
+            query_allowlist(deps.as_ref(), Some(ADDR1.to_string())),
+            vec![Addr::unchecked(ADDR2)]
+        );
+
+        // Remove ADDR2, should error again
+        let msg = UpdateAllowlist {
+            add: vec![],
+            remove: vec![ADDR2.to_string()],
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            draw.clone(),
+        )
+        .unwrap_err();
+        assert!(query_allowlist(deps.as_ref(), Some(ADDR1.to_string())).is_empty());
+
+        // Disable the allowlist, anyone can draw
+        let msg = UpdateAllowlistEnabled {
+            new_allowlist_enabled: false,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+        execute(deps.as_mut(), env, mock_info(ADDR2, &[]), draw).unwrap();
+    }
+
+    #[test]
+    fn test_ban_painter() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 200,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 2,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 10,
+            height: 10,
+            chunk_size: Some(8),
+        };
+        instantiate(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+
+        // ADDR2 draws 150 pixels across several chunks, ADDR1 draws over one
+        let pixels = (0..150)
+            .map(|i| PixelDraw {
+                chunk_x: i / 64,
+                chunk_y: 0,
+                x: i % 8,
+                y: i % 64 / 8,
+                color: 5,
+            })
+            .collect();
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+        let draw = Draw {
+            canvas_id: 0,
+            chunk_x: 0,
+            chunk_y: 0,
+            x: 0,
+            y: 0,
+            color: 3,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            draw.clone(),
+        )
+        .unwrap();
+
+        // Ban as ADDR2, should error
+        let ban = BanPainter {
+            address: ADDR2.to_string(),
+            erase: true,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            ban.clone(),
+        )
+        .unwrap_err();
+
+        // Ban as ADDR1, the first 100 pixels are erased
+        let res = execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), ban).unwrap();
+        assert_eq!(res.attributes[2].value, "false");
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), draw).unwrap_err();
+        assert!(matches!(err, ContractError::Banned {}));
+        let msg = QueryMsg::GetBanned {
+            start_after: None,
+            limit: None,
+        };
+        let res: BannedResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.addresses, vec![Addr::unchecked(ADDR2)]);
+
+        // Erase the rest
+        let msg = ErasePainter {
+            address: ADDR2.to_string(),
+            limit: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+        assert_eq!(res.attributes[2].value, "true");
+        for chunk_x in 0..3 {
+            let chunk = query_chunk(deps.as_ref(), env.clone(), chunk_x, 0);
+            for (y, row) in chunk.grid.iter().enumerate() {
+                for (x, pixel) in row.iter().enumerate() {
+                    if chunk_x == 0 && x == 0 && y == 0 {
+                        // Pixels owned by other painters are left alone
+                        assert_eq!(pixel.color, 3);
+                        assert_eq!(pixel.painter, Some(Addr::unchecked(ADDR1)));
+                    } else {
+                        assert_eq!(pixel.color, 0);
+                        assert_eq!(pixel.painter, None);
+                    }
+                }
+            }
+        }
+        let msg = QueryMsg::GetPainterStats {
+            canvas_id: 0,
+            address: ADDR2.to_string(),
+        };
+        let stats: PainterStats =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(stats.pixels_owned, 0);
+
+        // The erase is in the history, so rolling back to after it keeps the pixel blank
+        let history = query_pixel_history(deps.as_ref(), env.clone(), 1, 0, None);
+        assert_eq!(history.history.len(), 2);
+        assert_eq!(history.history[0].color, 0);
+        assert_eq!(history.history[0].painter, None);
+        let msg = RollbackRegion {
+            canvas_id: 0,
+            rect: Region {
+                x: 1,
+                y: 0,
+                width: 1,
+                height: 1,
+            },
+            to_height: env.block.height,
+            start_after: None,
+            limit: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+        let chunk = query_chunk(deps.as_ref(), env.clone(), 0, 0);
+        assert_eq!(chunk.grid[0][1].color, 0);
+        assert_eq!(chunk.grid[0][1].painter, None);
+
+        // Unban, can draw again
+        let msg = UnbanPainter {
+            address: ADDR2.to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+        let msg = ErasePainter {
+            address: ADDR2.to_string(),
+            limit: None,
+        };
+        execute(deps.as_mut(), env, mock_info(ADDR1, &[]), msg).unwrap_err();
+    }
+
+    #[test]
+    fn test_locked_regions() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 2,
+            height: 2,
+            chunk_size: Some(8),
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // A region spanning both chunks of the top row
+        let region = Region {
+            x: 6,
+            y: 2,
+            width: 4,
+            height: 2,
+        };
+        let lock = LockRegion {
+            canvas_id: 0,
+            region: region.clone(),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            lock.clone(),
+        )
+        .unwrap_err();
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), lock).unwrap();
+        assert_eq!(res.attributes[1].value, "0");
+
+        // Regions outside the canvas, should error
+        let msg = LockRegion {
+            canvas_id: 0,
+            region: Region {
+                x: 10,
+                y: 0,
+                width: 7,
+                height: 1,
+            },
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        let msg = LockRegion {
+            canvas_id: 0,
+            region: Region {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 1,
+            },
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+        let draw = |chunk_x: u64, x: u64, y: u64| Draw {
+            canvas_id: 0,
+            chunk_x,
+            chunk_y: 0,
+            x,
+            y,
+            color: 3,
+        };
+
+        // Inside the region in either chunk, should error
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), draw(0, 6, 2)).unwrap_err();
+        assert!(matches!(err, ContractError::RegionLocked { region_id: 0 }));
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(1, 1, 3)).unwrap_err();
+
+        // Just outside the region
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(0, 5, 2)).unwrap();
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(1, 2, 2)).unwrap();
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(1, 1, 4)).unwrap();
+
+        let msg = QueryMsg::GetLockedRegions {
+            canvas_id: 0,
+            start_after: None,
+            limit: None,
+        };
+        let res: LockedRegionsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg.clone()).unwrap()).unwrap();
+        assert_eq!(
+            res.regions,
+            vec![LockedRegion {
+                region_id: 0,
+                region
+            }]
+        );
+
+        // Unlock, can draw again
+        let unlock = UnlockRegion {
+            canvas_id: 0,
+            region_id: 0,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            unlock.clone(),
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), env.clone(), info.clone(), unlock.clone()).unwrap();
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(0, 6, 2)).unwrap();
+
+        // Unlock again, should error
+        let err = execute(deps.as_mut(), env.clone(), info, unlock).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::LockedRegionNotFound { region_id: 0 }
+        ));
+        let res: LockedRegionsResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert!(res.regions.is_empty());
+    }
+
+    #[test]
+    fn test_time_mode() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let now = env.block.time.seconds();
+
+        // End time in the past, should error even though it's a future height
+        let instantiate_msg = |end_height: u64| InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 60,
+            start_height: None,
+            end_height: Some(end_height),
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Time,
+            palette: None,
+            width: 100,
+            height: 100,
+            chunk_size: None,
+        };
+        let msg = instantiate_msg(env.block.height + 100);
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        let msg = instantiate_msg(now + 3600);
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let draw = Draw {
+            canvas_id: 0,
+            chunk_x: 0,
+            chunk_y: 0,
+            x: 0,
+            y: 0,
+            color: 3,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), draw.clone()).unwrap();
+
+        // Cooldown is counted in seconds
+        let cooldown = query_cooldown(deps.as_ref(), env.clone(), ADDR1.to_string());
+        assert_eq!(cooldown.current_cooldown, now + 60);
+
+        // Many blocks later but only 59 seconds, should error
+        let mut later = env.clone();
+        later.block.height += 1000;
+        later.block.time = later.block.time.plus_seconds(59);
+        execute(deps.as_mut(), later.clone(), info.clone(), draw.clone()).unwrap_err();
+        later.block.time = later.block.time.plus_seconds(1);
+        execute(deps.as_mut(), later.clone(), info.clone(), draw.clone()).unwrap();
+
+        // Past the end time, should error
+        later.block.time = env.block.time.plus_seconds(3601);
+        let err = execute(deps.as_mut(), later, info, draw).unwrap_err();
+        assert!(matches!(err, ContractError::EndHeightReached {}));
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 100,
+            height: 100,
+            chunk_size: None,
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let draw = Draw {
+            canvas_id: 0,
+            chunk_x: 0,
+            chunk_y: 0,
+            x: 0,
+            y: 0,
+            color: 3,
+        };
+
+        // Pause as ADDR2, should error
+        let pause = Pause {
+            reason: Some("Cleaning up spam".to_string()),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            pause.clone(),
+        )
+        .unwrap_err();
+
+        // Pause as ADDR1, drawing is rejected but queries work
+        execute(deps.as_mut(), env.clone(), info.clone(), pause).unwrap();
+        let config = query_config(deps.as_ref(), env.clone());
+        assert!(config.paused);
+        assert_eq!(config.pause_reason, Some("Cleaning up spam".to_string()));
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), draw.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Paused {}));
+        query_chunk(deps.as_ref(), env.clone(), 0, 0);
+
+        // Pausing again with a new reason records the old and new reason
+        let pause = Pause {
+            reason: Some("Spam wave".to_string()),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), pause).unwrap();
+        let reason = &res.events[1].attributes;
+        assert_eq!(reason[0].value, "pause_reason");
+        assert_eq!(reason[1].value, "\"Cleaning up spam\"");
+        assert_eq!(reason[2].value, "\"Spam wave\"");
+
+        // Unpause, can draw again
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            Unpause {},
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), env.clone(), info.clone(), Unpause {}).unwrap();
+        let config = query_config(deps.as_ref(), env.clone());
+        assert!(!config.paused);
+        assert_eq!(config.pause_reason, None);
+        execute(deps.as_mut(), env, info, draw).unwrap();
+    }
+
+    #[test]
+    fn test_roles() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = mock_info(ADDR1, &[]);
+        let operator = mock_info(ADDR2, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 30,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 100,
+            height: 100,
+            chunk_size: None,
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+
+        let update_cooldown = UpdateCooldown {
+            canvas_id: 0,
+            new_cooldown: 15,
+        };
+        let lock = LockRegion {
+            canvas_id: 0,
+            region: Region {
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 1,
+            },
+        };
+        let grant = |role: Role| GrantRole {
+            address: ADDR2.to_string(),
+            role,
+        };
+
+        // No roles yet, should error
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            operator.clone(),
+            update_cooldown.clone(),
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), env.clone(), operator.clone(), lock.clone()).unwrap_err();
+
+        // Only the admin can grant roles
+        let msg = grant(Role::ConfigManager);
+        execute(deps.as_mut(), env.clone(), operator.clone(), msg.clone()).unwrap_err();
+        execute(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+
+        // Config managers can change the cooldown but not lock regions
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            operator.clone(),
+            update_cooldown.clone(),
+        )
+        .unwrap();
+        assert_eq!(query_canvas(deps.as_ref(), env.clone(), 0).cooldown, 15);
+        execute(deps.as_mut(), env.clone(), operator.clone(), lock.clone()).unwrap_err();
+
+        // Moderators can lock regions
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            grant(Role::Moderator),
+        )
+        .unwrap();
+        execute(deps.as_mut(), env.clone(), operator.clone(), lock).unwrap();
+        let msg = QueryMsg::GetRoles {
+            start_after: None,
+            limit: None,
+        };
+        let res: RolesResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.operators,
+            vec![OperatorRoles {
+                address: Addr::unchecked(ADDR2),
+                roles: vec![Role::ConfigManager, Role::Moderator],
+            }]
+        );
+
+        // Neither role can withdraw the treasury or grant roles
+        let msg = WithdrawTreasury { recipient: None };
+        let err = execute(deps.as_mut(), env.clone(), operator.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            operator.clone(),
+            grant(Role::Moderator),
+        )
+        .unwrap_err();
+
+        // Revoke, config changes are rejected again
+        let msg = RevokeRole {
+            address: ADDR2.to_string(),
+            role: Role::ConfigManager,
+        };
+        execute(deps.as_mut(), env.clone(), admin, msg).unwrap();
+        execute(deps.as_mut(), env, operator, update_cooldown).unwrap_err();
+    }
+
+    #[test]
+    fn test_rollback_region() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 5,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 2,
+            height: 1,
+            chunk_size: Some(4),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+
+        let pixel = |chunk_x: u64, x: u64, color: u8| PixelDraw {
+            chunk_x,
+            chunk_y: 0,
+            x,
+            y: 0,
+            color,
+        };
+
+        // ADDR1 draws the artwork
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![pixel(0, 3, 5), pixel(1, 0, 5)],
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+
+        // ADDR2 raids it a block later, drawing over one pixel and a blank one
+        let mut raid = env.clone();
+        raid.block.height += 1;
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![pixel(0, 3, 3), pixel(1, 1, 3)],
+        };
+        execute(deps.as_mut(), raid.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+
+        // Rollback as ADDR2, should error
+        let rollback = |start_after: Option<u64>| RollbackRegion {
+            canvas_id: 0,
+            rect: Region {
+                x: 3,
+                y: 0,
+                width: 3,
+                height: 1,
+            },
+            to_height: env.block.height,
+            start_after,
+            limit: Some(2),
+        };
+        execute(
+            deps.as_mut(),
+            raid.clone(),
+            mock_info(ADDR2, &[]),
+            rollback(None),
+        )
+        .unwrap_err();
+
+        // First page restores the pixel drawn over, the untouched one is left alone
+        let res = execute(deps.as_mut(), raid.clone(), admin.clone(), rollback(None)).unwrap();
+        assert_eq!(res.attributes[1].value, "1");
+        assert_eq!(res.attributes[2].value, "false");
+        assert_eq!(res.attributes[3].value, "1");
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "rollback_pixel");
+        let previous_painter = res.events[0]
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "previous_painter")
+            .unwrap();
+        assert_eq!(previous_painter.value, ADDR2);
+        let chunk = query_chunk(deps.as_ref(), env.clone(), 0, 0);
+        assert_eq!(chunk.grid[0][3].color, 5);
+        assert_eq!(chunk.grid[0][3].painter, Some(Addr::unchecked(ADDR1)));
+
+        // Second page clears the pixel that was blank
+        let res = execute(
+            deps.as_mut(),
+            raid.clone(),
+            admin.clone(),
+            rollback(Some(1)),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2].value, "true");
+        let chunk = query_chunk(deps.as_ref(), env.clone(), 1, 0);
+        assert_eq!(chunk.grid[0][0].color, 5);
+        assert_eq!(chunk.grid[0][1].color, 0);
+        assert_eq!(chunk.grid[0][1].painter, None);
+
+        // The rollback is recorded in the history
+        let history = query_pixel_history(deps.as_ref(), env.clone(), 5, 0, None);
+        assert_eq!(history.history.len(), 2);
+        assert_eq!(history.history[0].painter, None);
+        let msg = QueryMsg::GetPainterStats {
+            canvas_id: 0,
+            address: ADDR2.to_string(),
+        };
+        let stats: PainterStats =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(stats.pixels_owned, 0);
+
+        // Art drawn while the history was off is restored from what the first
+        // recorded draw replaced
+        let msg = UpdateHistoryDepth {
+            new_history_depth: 0,
+        };
+        execute(deps.as_mut(), raid.clone(), admin.clone(), msg).unwrap();
+        let mut art = raid.clone();
+        art.block.height += 1;
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![pixel(0, 2, 5)],
+        };
+        execute(deps.as_mut(), art.clone(), admin.clone(), msg).unwrap();
+        let msg = UpdateHistoryDepth {
+            new_history_depth: 5,
+        };
+        execute(deps.as_mut(), art.clone(), admin.clone(), msg).unwrap();
+        let mut raid = art.clone();
+        raid.block.height += 1;
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![pixel(0, 2, 3)],
+        };
+        execute(deps.as_mut(), raid.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+        let msg = RollbackRegion {
+            canvas_id: 0,
+            rect: Region {
+                x: 2,
+                y: 0,
+                width: 1,
+                height: 1,
+            },
+            to_height: art.block.height,
+            start_after: None,
+            limit: None,
+        };
+        execute(deps.as_mut(), raid, admin, msg).unwrap();
+        let chunk = query_chunk(deps.as_ref(), env, 0, 0);
+        assert_eq!(chunk.grid[0][2].color, 5);
+        assert_eq!(chunk.grid[0][2].painter, Some(Addr::unchecked(ADDR1)));
+    }
+
+    #[test]
+    fn test_get_region() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 20,
+            height: 20,
+            chunk_size: Some(4),
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // One pixel in each of the four chunks around (4, 4)
+        let pixel = |chunk_x: u64, chunk_y: u64, x: u64, y: u64, color: u8| PixelDraw {
+            chunk_x,
+            chunk_y,
+            x,
+            y,
+            color,
+        };
+        let msg = DrawBatch {
+            canvas_id: 0,
+            pixels: vec![
+                pixel(0, 0, 3, 3, 1),
+                pixel(1, 0, 0, 3, 2),
+                pixel(0, 1, 3, 0, 3),
+                pixel(1, 1, 0, 0, 4),
+            ],
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let get_region = |width: u64, height: u64, include_painters: bool| QueryMsg::GetRegion {
+            canvas_id: 0,
+            x: 3,
+            y: 3,
+            width,
+            height,
+            include_painters,
+        };
+        let res: RegionResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), get_region(3, 2, true)).unwrap())
+                .unwrap();
+        assert_eq!(res.colors, Binary(vec![1, 2, 0, 3, 4, 0]));
+        let painter = Some(Addr::unchecked(ADDR1));
+        assert_eq!(
+            res.painters,
+            Some(vec![
+                painter.clone(),
+                painter.clone(),
+                None,
+                painter.clone(),
+                painter,
+                None
+            ])
+        );
+
+        // Painters are left out unless asked for
+        let res: RegionResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), get_region(3, 2, false)).unwrap())
+                .unwrap();
+        assert_eq!(res.painters, None);
+
+        // Outside the canvas or too large, should error
+        query(deps.as_ref(), env.clone(), get_region(78, 1, false)).unwrap_err();
+        query(deps.as_ref(), env.clone(), get_region(0, 1, false)).unwrap_err();
+        query(deps.as_ref(), env.clone(), get_region(77, 77, false)).unwrap_err();
+        query(deps.as_ref(), env, get_region(64, 64, false)).unwrap();
+    }
+
+    #[test]
+    fn test_list_chunks() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 10,
+            height: 10,
+            chunk_size: Some(4),
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Chunks left over from 0.1.0
+        let grid = vec![
+            vec![
+                PixelInfo {
+                    color: 0,
+                    painter: None
+                };
+                4
+            ];
+            4
+        ];
+        for key in [(0, 0), (2, 0)] {
+            v0_1::CHUNKS
+                .save(deps.as_mut().storage, key, &grid)
+                .unwrap();
+        }
+
+        let draw = |chunk_x: u64, chunk_y: u64| Draw {
+            canvas_id: 0,
+            chunk_x,
+            chunk_y,
+            x: 0,
+            y: 0,
+            color: 3,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), draw(1, 0)).unwrap();
+        let mut later = env.clone();
+        later.block.height += 1;
+        execute(deps.as_mut(), later.clone(), info, draw(0, 1)).unwrap();
+
+        let list_chunks = |deps: Deps, canvas_id: u64, start_after: Option<(u64, u64)>| {
+            let msg = QueryMsg::ListChunks {
+                canvas_id,
+                start_after,
+                limit: Some(2),
+            };
+            let res: ChunksResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.chunks
+        };
+        let chunk = |x: u64, y: u64, last_modified: Option<u64>| ChunkInfo {
+            x,
+            y,
+            last_modified,
+        };
+        assert_eq!(
+            list_chunks(deps.as_ref(), 0, None),
+            vec![chunk(0, 0, None), chunk(0, 1, Some(later.block.height))]
+        );
+        assert_eq!(
+            list_chunks(deps.as_ref(), 0, Some((0, 1))),
+            vec![chunk(1, 0, Some(env.block.height)), chunk(2, 0, None)]
+        );
+        assert!(list_chunks(deps.as_ref(), 0, Some((2, 0))).is_empty());
+
+        // Other canvases don't see the legacy chunks
+        let msg = CreateCanvas {
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            width: 10,
+            height: 10,
+            chunk_size: None,
+        };
+        execute(deps.as_mut(), env, mock_info(ADDR1, &[]), msg).unwrap();
+        assert!(list_chunks(deps.as_ref(), 1, None).is_empty());
+    }
+
+    #[test]
+    fn test_events() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 2,
+            height: 2,
+            chunk_size: Some(4),
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let draw = Draw {
+            canvas_id: 0,
+            chunk_x: 1,
+            chunk_y: 0,
+            x: 2,
+            y: 3,
+            color: 5,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), draw).unwrap();
+
+        // A blank pixel has no previous painter, which is left out rather than empty
+        let attributes = &res.events[0].attributes;
+        assert!(attributes.iter().all(|attr| !attr.value.is_empty()));
+        assert!(!attributes.iter().any(|attr| attr.key == "previous_painter"));
+        assert_eq!(attributes.last().unwrap().key, "previous_color");
+
+        // ADDR2 draws over the pixel, the event shows what was there before
+        let draw = Draw {
+            canvas_id: 0,
+            chunk_x: 1,
+            chunk_y: 0,
+            x: 2,
+            y: 3,
+            color: 8,
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), draw).unwrap();
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "draw_pixel");
+        let attributes: Vec<(&str, &str)> = res.events[0]
+            .attributes
+            .iter()
+            .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("canvas_id", "0"),
+                ("chunk_x", "1"),
+                ("chunk_y", "0"),
+                ("x", "2"),
+                ("y", "3"),
+                ("global_x", "6"),
+                ("global_y", "3"),
+                ("color", "8"),
+                ("painter", ADDR2),
+                ("previous_color", "5"),
+                ("previous_painter", ADDR1),
+            ]
+        );
+
+        // Config changes record the old and new values
+        let msg = UpdateCooldown {
+            canvas_id: 0,
+            new_cooldown: 30,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(res.events[0].ty, "config_change");
+        let attributes: Vec<(&str, &str)> = res.events[0]
+            .attributes
+            .iter()
+            .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("field", "cooldown"),
+                ("old", "0"),
+                ("new", "30"),
+                ("canvas_id", "0"),
+            ]
+        );
+
+        let msg = GrantRole {
+            address: ADDR2.to_string(),
+            role: Role::Moderator,
+        };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        let attributes: Vec<(&str, &str)> = res.events[0]
+            .attributes
+            .iter()
+            .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("field", "roles"),
+                ("old", "[]"),
+                ("new", "[\"moderator\"]"),
+                ("address", ADDR2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_draw_at() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 2,
+            height: 3,
+            chunk_size: Some(4),
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let draw_at = |x: u64, y: u64| DrawAt {
+            canvas_id: 0,
+            x,
+            y,
+            color: 5,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), draw_at(6, 9)).unwrap();
+
+        // Lands in chunk (1, 2) at (2, 1)
+        let chunk = query_chunk(deps.as_ref(), env.clone(), 1, 2);
+        assert_eq!(chunk.grid[1][2].color, 5);
+
+        let get_pixel = |deps: Deps, x: u64, y: u64| {
+            query(deps, env.clone(), QueryMsg::GetPixel { canvas_id: 0, x, y })
+        };
+        let pixel: PixelInfo = from_binary(&get_pixel(deps.as_ref(), 6, 9).unwrap()).unwrap();
+        assert_eq!(
+            pixel,
+            PixelInfo {
+                color: 5,
+                painter: Some(Addr::unchecked(ADDR1)),
+            }
+        );
+        let pixel: PixelInfo = from_binary(&get_pixel(deps.as_ref(), 7, 9).unwrap()).unwrap();
+        assert_eq!(pixel.painter, None);
+
+        // The canvas is 8 pixels wide and 12 high
+        let err = execute(deps.as_mut(), env.clone(), info, draw_at(8, 0)).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::PixelOutOfBounds {
+                x: 8,
+                y: 0,
+                width: 8,
+                height: 12,
+            }
+        ));
+        let err = get_pixel(deps.as_ref(), 0, 12).unwrap_err();
+        assert!(err.to_string().contains("y below 12"));
+    }
+
+    #[test]
+    fn test_can_draw() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 10,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: Some(coin(5, "ujuno")),
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 2,
+            height: 2,
+            chunk_size: Some(4),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+
+        let can_draw = |deps: Deps, x: u64| {
+            let msg = QueryMsg::CanDraw {
+                canvas_id: 0,
+                address: ADDR2.to_string(),
+                x,
+                y: 0,
+            };
+            let res: CanDrawResponse =
+                from_binary(&query(deps, env.clone(), msg).unwrap()).unwrap();
+            res.reason
+        };
+
+        let required = coin(5, "ujuno");
+        assert_eq!(
+            can_draw(deps.as_ref(), 0),
+            Some(DrawRejection::InsufficientFunds { required })
+        );
+        deps.querier.update_balance(ADDR2, coins(5, "ujuno"));
+        assert_eq!(can_draw(deps.as_ref(), 0), None);
+        assert_eq!(
+            can_draw(deps.as_ref(), 8),
+            Some(DrawRejection::OutOfBounds {
+                width: 8,
+                height: 8
+            })
+        );
+
+        let msg = DrawAt {
+            canvas_id: 0,
+            x: 0,
+            y: 0,
+            color: 3,
+        };
+        let info = mock_info(ADDR2, &coins(5, "ujuno"));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(
+            can_draw(deps.as_ref(), 0),
+            Some(DrawRejection::Cooldown { remaining: 10 })
+        );
+
+        // Once the canvas has ended that is the reason, even while on cooldown
+        let msg = UpdateEndHeight {
+            canvas_id: 0,
+            new_end_height: Some(env.block.height + 1),
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+        let mut ended = env.clone();
+        ended.block.height += 2;
+        let msg = QueryMsg::CanDraw {
+            canvas_id: 0,
+            address: ADDR2.to_string(),
+            x: 0,
+            y: 0,
+        };
+        let res: CanDrawResponse = from_binary(&query(deps.as_ref(), ended, msg).unwrap()).unwrap();
+        assert_eq!(res.reason, Some(DrawRejection::Ended {}));
+        let msg = UpdateEndHeight {
+            canvas_id: 0,
+            new_end_height: None,
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+
+        let msg = Pause {
+            reason: Some("maintenance".to_string()),
+        };
+        execute(deps.as_mut(), env.clone(), admin, msg).unwrap();
+        assert_eq!(
+            can_draw(deps.as_ref(), 0),
+            Some(DrawRejection::Paused {
+                reason: Some("maintenance".to_string())
+            })
+        );
+
+        // Invalid addresses are an error rather than a panic
+        let msg = QueryMsg::GetCooldown {
+            canvas_id: 0,
+            address: "ADDR".to_string(),
+        };
+        query(deps.as_ref(), env, msg).unwrap_err();
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 5,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 2,
+            height: 2,
+            chunk_size: Some(4),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+
+        let draw = |chunk_x: u64, chunk_y: u64| Draw {
+            canvas_id: 0,
+            chunk_x,
+            chunk_y,
+            x: 1,
+            y: 1,
+            color: 5,
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), draw(1, 1)).unwrap();
+
+        let resize = |width: u64, height: u64, offset_x: i64, offset_y: i64, force: bool| Resize {
+            canvas_id: 0,
+            width,
+            height,
+            offset_x,
+            offset_y,
+            force,
+        };
+
+        // Only config managers can resize
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            resize(3, 3, 0, 0, false),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            resize(0, 3, 0, 0, false),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidDimensions {}));
+
+        // Growing keeps the art where it was
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            resize(3, 4, 0, 0, false),
+        )
+        .unwrap();
+        assert_eq!(query_dimensions(deps.as_ref(), env.clone()).height, 4);
+        assert_eq!(
+            query_chunk(deps.as_ref(), env.clone(), 1, 1).grid[1][1].color,
+            5
+        );
+        execute(deps.as_mut(), env.clone(), admin.clone(), draw(2, 3)).unwrap();
+
+        // Dropping art needs force, blank chunks can go without it
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            resize(2, 3, 0, 0, false),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ChunkNotEmpty { x: 2, y: 3 }));
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            resize(2, 3, 0, 0, true),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[1].value, "1");
+        assert_eq!(res.events.len(), 2);
+        assert_eq!(res.events[1].ty, "drop_pixel");
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            resize(1, 1, 0, 0, false),
+        )
+        .unwrap_err();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            resize(2, 2, 0, 0, false),
+        )
+        .unwrap();
+
+        // Growing again doesn't bring back the dropped art or its history
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            resize(3, 4, 0, 0, false),
+        )
+        .unwrap();
+        assert_eq!(
+            query_chunk(deps.as_ref(), env.clone(), 2, 3).grid[1][1].color,
+            0
+        );
+        assert!(query_pixel_history(deps.as_ref(), env.clone(), 9, 13, None)
+            .history
+            .is_empty());
+
+        // Growing the left and top edges moves the art, its history and locks
+        let msg = LockRegion {
+            canvas_id: 0,
+            region: Region {
+                x: 4,
+                y: 4,
+                width: 2,
+                height: 2,
+            },
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            resize(4, 5, 1, 1, false),
+        )
+        .unwrap();
+        assert_eq!(
+            query_chunk(deps.as_ref(), env.clone(), 2, 2).grid[1][1].color,
+            5
+        );
+        assert_eq!(
+            query_chunk(deps.as_ref(), env.clone(), 1, 1).grid[1][1].color,
+            0
+        );
+        let history = query_pixel_history(deps.as_ref(), env.clone(), 9, 9, None);
+        assert_eq!(history.history.len(), 1);
+        assert!(query_pixel_history(deps.as_ref(), env.clone(), 5, 5, None)
+            .history
+            .is_empty());
+        let msg = QueryMsg::GetLockedRegions {
+            canvas_id: 0,
+            start_after: None,
+            limit: None,
+        };
+        let res: LockedRegionsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.regions[0].region,
+            Region {
+                x: 8,
+                y: 8,
+                width: 2,
+                height: 2,
+            }
+        );
+
+        // Shrinking the left and top edges moves it back the other way
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin,
+            resize(1, 1, -2, -2, false),
+        )
+        .unwrap();
+        assert_eq!(
+            query_chunk(deps.as_ref(), env.clone(), 0, 0).grid[1][1].color,
+            5
+        );
+        let msg = QueryMsg::GetPainterStats {
+            canvas_id: 0,
+            address: ADDR1.to_string(),
+        };
+        let stats: PainterStats = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(stats.pixels_owned, 1);
+    }
+
+    #[test]
+    fn test_resize_moved_chunks() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let admin = mock_info(ADDR1, &[]);
+
+        let msg = InstantiateMsg {
+            admin_address: ADDR1.to_string(),
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            batch_cooldown: BatchCooldown::PerPixel,
+            max_batch_size: 10,
+            price: None,
+            accepted_cw20: vec![],
+            history_depth: 0,
+            allowlist_enabled: false,
+            clock_mode: ClockMode::Height,
+            palette: None,
+            width: 2,
+            height: 2,
+            chunk_size: Some(4),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+
+        // A chunk left over from 0.1.0 still shows up after it moved
+        let mut grid = vec![
+            vec![
+                PixelInfo {
+                    color: 0,
+                    painter: None
+                };
+                4
+            ];
+            4
+        ];
+        grid[1][1].color = 3;
+        v0_1::CHUNKS
+            .save(deps.as_mut().storage, (0, 0), &grid)
+            .unwrap();
+        env.block.height += 1;
+        let msg = Resize {
+            canvas_id: 0,
+            width: 3,
+            height: 2,
+            offset_x: 1,
+            offset_y: 0,
+            force: false,
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+        let msg = QueryMsg::ListChunks {
+            canvas_id: 0,
+            start_after: None,
+            limit: None,
+        };
+        let res: ChunksResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.chunks,
+            vec![ChunkInfo {
+                x: 1,
+                y: 0,
+                last_modified: Some(env.block.height),
+            }]
+        );
+        assert_eq!(
+            query_chunk(deps.as_ref(), env.clone(), 1, 0).grid[1][1].color,
+            3
+        );
+
+        // A whole chunk is as much as a resize can move on this canvas
+        let msg = CreateCanvas {
+            cooldown: 0,
+            start_height: None,
+            end_height: None,
+            width: 2,
+            height: 1,
+            chunk_size: Some(64),
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+        for chunk_x in 0..2 {
+            let msg = Draw {
+                canvas_id: 1,
+                chunk_x,
+                chunk_y: 0,
+                x: 0,
+                y: 0,
+                color: 5,
+            };
+            execute(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+        }
+        let resize = |width: u64, offset_x: i64| Resize {
+            canvas_id: 1,
+            width,
+            height: 1,
+            offset_x,
+            offset_y: 0,
+            force: true,
+        };
+        let err = execute(deps.as_mut(), env.clone(), admin.clone(), resize(3, 1)).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::ResizeTooLarge { max_chunks: 1 }
+        ));
+        execute(deps.as_mut(), env.clone(), admin.clone(), resize(1, 0)).unwrap();
+        execute(deps.as_mut(), env, admin, resize(2, 1)).unwrap();
     }
 }
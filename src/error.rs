@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Coin, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,21 +6,111 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Semver(#[from] semver::Error),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
-    #[error("End height must be greater than the current block height")]
+    #[error("No admin transfer has been proposed")]
+    NoPendingAdmin {},
+
+    #[error("Admin transfer has expired")]
+    PendingAdminExpired {},
+
+    #[error("Drawing is paused")]
+    Paused {},
+
+    #[error("Address is not on the allowlist")]
+    NotOnAllowlist {},
+
+    #[error("Address is banned from drawing")]
+    Banned {},
+
+    #[error("Address is not banned")]
+    NotBanned {},
+
+    #[error("Region must be non-empty and inside the canvas")]
+    InvalidRegion {},
+
+    #[error("Locked region {region_id} does not exist")]
+    LockedRegionNotFound { region_id: u64 },
+
+    #[error("Pixel is inside locked region {region_id}")]
+    RegionLocked { region_id: u64 },
+
+    #[error("End height must be after the current block")]
     InvalidEndHeight {},
 
+    #[error("Start height must be before the end height")]
+    InvalidStartHeight {},
+
+    #[error("Canvas opens for drawing at {start_height}")]
+    NotStarted { start_height: u64 },
+
     #[error("Invalid coordinates, must be within the width and height of the grid")]
     InvalidCoordinates {},
 
-    #[error("Invalid color, color code must be between 0 and 15")]
+    #[error(
+        "Pixel ({x}, {y}) is outside the canvas, x must be below {width} and y below {height}"
+    )]
+    PixelOutOfBounds {
+        x: u64,
+        y: u64,
+        width: u64,
+        height: u64,
+    },
+
+    #[error("Canvas {canvas_id} does not exist")]
+    CanvasNotFound { canvas_id: u64 },
+
+    #[error(
+        "Canvas must be at least one chunk wide and high, and small enough to address every pixel"
+    )]
+    InvalidDimensions {},
+
+    #[error("Chunk ({x}, {y}) has been drawn on, resize with force to drop it")]
+    ChunkNotEmpty { x: u64, y: u64 },
+
+    #[error("Resize can move or drop at most {max_chunks} drawn chunks at once")]
+    ResizeTooLarge { max_chunks: u64 },
+
+    #[error("Invalid chunk size, must be between 1 and {max}")]
+    InvalidChunkSize { max: u64 },
+
+    #[error("Invalid color, color code must be an index into the palette")]
     InvalidColor {},
 
+    #[error("Invalid palette, must have 1 to 256 colors formatted as #RRGGBB")]
+    InvalidPalette {},
+
     #[error("This address is still on cooldown, please wait until you can draw again")]
-    StillOnCooldown {},
+    StillOnCooldown { remaining: u64 },
 
     #[error("The end height of this grid has been reached, drawing is no longer allowed")]
     EndHeightReached {},
+
+    #[error("A batch must contain at least one pixel")]
+    EmptyBatch {},
+
+    #[error("Batch too large, at most {max} pixels can be drawn at once")]
+    BatchTooLarge { max: u64 },
+
+    #[error("Insufficient funds, drawing requires {required}")]
+    InsufficientFunds { required: Coin },
+
+    #[error("Insufficient tokens, drawing requires {required} of {token}")]
+    InsufficientTokens { token: String, required: Uint128 },
+
+    #[error("This CW20 token is not accepted as payment")]
+    UnacceptedToken {},
+
+    #[error("The treasury is empty, there is nothing to withdraw")]
+    EmptyTreasury {},
+
+    #[error("Cannot migrate from a different contract: {name}")]
+    InvalidContractName { name: String },
+
+    #[error("Cannot migrate from version {stored} down to {current}")]
+    CannotDowngrade { stored: String, current: String },
 }
@@ -1,6 +1,7 @@
 pub mod contract;
 pub mod contract_tests;
 mod error;
+mod migrations;
 pub mod msg;
 pub mod state;
 
@@ -0,0 +1,163 @@
+use cosmwasm_std::{StdResult, Storage};
+use semver::Version;
+
+use crate::error::ContractError;
+use crate::state::{
+    intern_painter, BatchCooldown, Canvas, Chunk, ClockMode, Config, Dimensions, PixelInfo,
+    CANVASES, CANVAS_COUNT, CHUNKS, CHUNK_LAST_MODIFIED, CONFIG, DEFAULT_PALETTE, PALETTE,
+};
+
+/// Storage layouts written by older versions of the contract.
+pub(crate) mod v0_1 {
+    use cosmwasm_std::Addr;
+    use cw_storage_plus::{Item, Map};
+    use serde::{Deserialize, Serialize};
+
+    use crate::state::PixelInfo;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct Config {
+        pub admin_address: Addr,
+        pub cooldown: u64,
+        pub end_height: Option<u64>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct Dimensions {
+        pub width: u64,
+        pub height: u64,
+    }
+
+    pub const CONFIG: Item<Config> = Item::new("config");
+    pub const DIMENSIONS: Item<Dimensions> = Item::new("dimensions");
+    // Chunks as nested JSON, these belong to canvas 0 and are converted lazily
+    // the first time they are drawn on
+    pub const CHUNKS: Map<(u64, u64), Vec<Vec<PixelInfo>>> = Map::new("chunks");
+}
+
+/// Loads a chunk, converting it from the nested JSON format written by 0.1.0
+/// if needed. The legacy entry is only removed once the chunk is saved with `save_chunk`.
+pub fn load_chunk(
+    storage: &mut dyn Storage,
+    canvas_id: u64,
+    key: (u64, u64),
+) -> StdResult<Option<Chunk>> {
+    if let Some(chunk) = CHUNKS.may_load(storage, (canvas_id, key.0, key.1))? {
+        return Ok(Some(chunk));
+    }
+
+    let grid = match load_legacy_grid(storage, canvas_id, key)? {
+        Some(grid) => grid,
+        None => return Ok(None),
+    };
+    let size = grid.len();
+    let mut chunk = Chunk::empty(size * size);
+    for (y, row) in grid.iter().enumerate() {
+        for (x, pixel) in row.iter().enumerate() {
+            let painter_id = match &pixel.painter {
+                Some(painter) => intern_painter(storage, painter)?,
+                None => 0,
+            };
+            chunk.paint(y * size + x, pixel.color, painter_id);
+        }
+    }
+    Ok(Some(chunk))
+}
+
+/// Loads a chunk written by 0.1.0 that has not been converted yet.
+pub fn load_legacy_grid(
+    storage: &dyn Storage,
+    canvas_id: u64,
+    key: (u64, u64),
+) -> StdResult<Option<Vec<Vec<PixelInfo>>>> {
+    if canvas_id != 0 {
+        return Ok(None);
+    }
+    v0_1::CHUNKS.may_load(storage, key)
+}
+
+/// Saves a chunk in the packed format, recording the block height it changed at.
+pub fn save_chunk(
+    storage: &mut dyn Storage,
+    canvas_id: u64,
+    key: (u64, u64),
+    chunk: &Chunk,
+    height: u64,
+) -> StdResult<()> {
+    if canvas_id == 0 && v0_1::CHUNKS.has(storage, key) {
+        v0_1::CHUNKS.remove(storage, key);
+    }
+    CHUNK_LAST_MODIFIED.save(storage, (canvas_id, key.0, key.1), &height)?;
+    CHUNKS.save(storage, (canvas_id, key.0, key.1), chunk)
+}
+
+/// A step that upgrades storage written by any version older than `version`.
+struct Migration {
+    version: &'static str,
+    run: fn(&mut dyn Storage) -> StdResult<()>,
+}
+
+/// Steps are run in order, so they must be sorted by version.
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: "0.2.0",
+    run: migrate_v0_2_0,
+}];
+
+/// Runs every step newer than the stored version, returning the versions applied.
+pub fn run_migrations(
+    storage: &mut dyn Storage,
+    stored_version: &Version,
+) -> Result<Vec<&'static str>, ContractError> {
+    let mut applied = vec![];
+    for migration in MIGRATIONS {
+        if *stored_version < Version::parse(migration.version)? {
+            (migration.run)(storage)?;
+            applied.push(migration.version);
+        }
+    }
+
+    Ok(applied)
+}
+
+/// 0.2.0 added batch drawing, payments and pixel history to the config,
+/// moved the palette on chain and hosts several canvases. The existing
+/// canvas becomes canvas 0, its cooldowns are not carried over.
+fn migrate_v0_2_0(storage: &mut dyn Storage) -> StdResult<()> {
+    let old_config = v0_1::CONFIG.load(storage)?;
+    let config = Config {
+        admin_address: old_config.admin_address,
+        batch_cooldown: BatchCooldown::PerPixel,
+        // Keep one pixel per transaction until the admin opts in to batches
+        max_batch_size: 1,
+        price: None,
+        accepted_cw20: vec![],
+        history_depth: 0,
+        allowlist_enabled: false,
+        clock_mode: ClockMode::Height,
+        paused: false,
+        pause_reason: None,
+    };
+    CONFIG.save(storage, &config)?;
+
+    let old_dimensions = v0_1::DIMENSIONS.load(storage)?;
+    let canvas = Canvas {
+        dimensions: Dimensions {
+            width: old_dimensions.width,
+            height: old_dimensions.height,
+            // Chunks were always 32x32 before the size was configurable
+            chunk_size: 32,
+        },
+        cooldown: old_config.cooldown,
+        start_height: None,
+        end_height: old_config.end_height,
+    };
+    CANVASES.save(storage, 0, &canvas)?;
+    CANVAS_COUNT.save(storage, &1)?;
+    v0_1::DIMENSIONS.remove(storage);
+
+    let palette = DEFAULT_PALETTE
+        .iter()
+        .map(|color| color.to_string())
+        .collect();
+    PALETTE.save(storage, &palette)
+}
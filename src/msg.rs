@@ -1,46 +1,318 @@
-use crate::state::PixelInfo;
+use crate::state::{
+    BatchCooldown, Canvas, ClockMode, PainterStats, PendingAdmin, PixelInfo, Region, Role,
+};
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+use cw20::{Cw20ReceiveMsg, Expiration};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub admin_address: String,
+    /// Cooldown of the first canvas, created with id 0
     pub cooldown: u64,
+    /// Start height of the first canvas, created with id 0
+    pub start_height: Option<u64>,
+    /// End height of the first canvas, created with id 0
     pub end_height: Option<u64>,
+    pub batch_cooldown: BatchCooldown,
+    pub max_batch_size: u64,
+    pub price: Option<Coin>,
+    pub accepted_cw20: Vec<AcceptedCw20>,
+    pub history_depth: u64,
+    /// Restrict drawing to the allowlist, which starts out empty.
+    pub allowlist_enabled: bool,
+    /// Count cooldowns, start and end heights in blocks or seconds, can't be
+    /// changed after instantiation.
+    pub clock_mode: ClockMode,
+    /// RGB hex values such as "#FF0000", defaults to 16 colors if not set.
+    pub palette: Option<Vec<String>>,
     /// How many chunks wide
     pub width: u64,
     /// How many chunks high
     pub height: u64,
+    /// How many pixels along each edge of a chunk, defaults to 32
+    pub chunk_size: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Draw {
+        canvas_id: u64,
         chunk_x: u64,
         chunk_y: u64,
         x: u64,
         y: u64,
         color: u8,
     },
-    UpdateAdmin {
+    DrawBatch {
+        canvas_id: u64,
+        pixels: Vec<PixelDraw>,
+    },
+    /// Draws a pixel by its global coordinates instead of its chunk.
+    DrawAt {
+        canvas_id: u64,
+        x: u64,
+        y: u64,
+        color: u8,
+    },
+    /// Pay for a draw with an accepted CW20 token, the embedded
+    /// message must be a `ReceiveMsg`.
+    Receive(Cw20ReceiveMsg),
+    CreateCanvas {
+        cooldown: u64,
+        start_height: Option<u64>,
+        end_height: Option<u64>,
+        /// How many chunks wide
+        width: u64,
+        /// How many chunks high
+        height: u64,
+        /// How many pixels along each edge of a chunk, defaults to 32
+        chunk_size: Option<u64>,
+    },
+    /// Offers the admin role to a new address, which has to accept it
+    /// before it expires. Replaces any earlier proposal.
+    ProposeAdmin {
         new_admin_address: String,
+        expires: Expiration,
+    },
+    AcceptAdmin {},
+    GrantRole {
+        address: String,
+        role: Role,
+    },
+    RevokeRole {
+        address: String,
+        role: Role,
     },
     UpdateCooldown {
+        canvas_id: u64,
         new_cooldown: u64,
     },
+    UpdateStartHeight {
+        canvas_id: u64,
+        new_start_height: Option<u64>,
+    },
     UpdateEndHeight {
+        canvas_id: u64,
         new_end_height: Option<u64>,
     },
+    /// Moves the art by the offsets then resizes the canvas, so any edge can
+    /// grow or shrink: growing the left edge by one chunk is a resize one chunk
+    /// wider with `offset_x` 1. Dropping chunks that have been drawn on fails
+    /// unless `force` is set. At most 4096 pixels worth of drawn chunks, and
+    /// at least one chunk, can move or be dropped in one resize.
+    Resize {
+        canvas_id: u64,
+        /// How many chunks wide
+        width: u64,
+        /// How many chunks high
+        height: u64,
+        /// Chunks to move the art right, negative moves it left
+        offset_x: i64,
+        /// Chunks to move the art down, negative moves it up
+        offset_y: i64,
+        force: bool,
+    },
+    UpdateBatchConfig {
+        new_batch_cooldown: BatchCooldown,
+        new_max_batch_size: u64,
+    },
+    UpdatePrice {
+        new_price: Option<Coin>,
+    },
+    UpdateAcceptedCw20 {
+        new_accepted_cw20: Vec<AcceptedCw20>,
+    },
+    UpdatePalette {
+        new_palette: Vec<String>,
+    },
+    ExtendPalette {
+        colors: Vec<String>,
+    },
+    UpdateHistoryDepth {
+        new_history_depth: u64,
+    },
+    UpdateAllowlistEnabled {
+        new_allowlist_enabled: bool,
+    },
+    /// Adds and removes addresses in bulk, removals are applied last.
+    UpdateAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Blocks the address from drawing. If `erase` is set the pixels it owns
+    /// are reset to color 0, continue with `ErasePainter` if they don't all fit.
+    BanPainter {
+        address: String,
+        erase: bool,
+    },
+    UnbanPainter {
+        address: String,
+    },
+    /// Resets up to `limit` more pixels owned by a banned address to color 0.
+    ErasePainter {
+        address: String,
+        limit: Option<u32>,
+    },
+    /// Restores each pixel in `rect` to how it was at block height `to_height`,
+    /// using the pixel history. Covers `limit` pixels in row-major order,
+    /// pass the `last_pixel` attribute as `start_after` to continue.
+    RollbackRegion {
+        canvas_id: u64,
+        rect: Region,
+        to_height: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Stops anyone from drawing inside the region.
+    LockRegion {
+        canvas_id: u64,
+        region: Region,
+    },
+    UnlockRegion {
+        canvas_id: u64,
+        region_id: u64,
+    },
+    /// Stops all drawing until `Unpause`.
+    Pause {
+        reason: Option<String>,
+    },
+    Unpause {},
+    WithdrawTreasury {
+        /// Defaults to the admin address if not set.
+        recipient: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    Draw {
+        canvas_id: u64,
+        chunk_x: u64,
+        chunk_y: u64,
+        x: u64,
+        y: u64,
+        color: u8,
+    },
+    DrawBatch {
+        canvas_id: u64,
+        pixels: Vec<PixelDraw>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AcceptedCw20 {
+    /// Address of the CW20 token contract.
+    pub token: String,
+    /// Price to draw a single pixel, in the token's smallest unit.
+    pub price: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PixelDraw {
+    pub chunk_x: u64,
+    pub chunk_y: u64,
+    pub x: u64,
+    pub y: u64,
+    pub color: u8,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    GetChunk { x: u64, y: u64 },
+    GetChunk {
+        canvas_id: u64,
+        x: u64,
+        y: u64,
+    },
+    /// A rectangle of pixels in global pixel coordinates, which can span
+    /// several chunks. At most 4096 pixels can be read at once.
+    GetRegion {
+        canvas_id: u64,
+        x: u64,
+        y: u64,
+        width: u64,
+        height: u64,
+        include_painters: bool,
+    },
+    /// A single pixel by its global coordinates.
+    GetPixel {
+        canvas_id: u64,
+        x: u64,
+        y: u64,
+    },
+    /// Chunks that have been drawn on, ordered by (x, y).
+    ListChunks {
+        canvas_id: u64,
+        start_after: Option<(u64, u64)>,
+        limit: Option<u32>,
+    },
     GetConfig {},
-    GetDimensions {},
-    GetCooldown { address: String },
+    GetPendingAdmin {},
+    /// Addresses holding a role, the admin is not listed.
+    GetRoles {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetCanvas {
+        canvas_id: u64,
+    },
+    ListCanvases {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetDimensions {
+        canvas_id: u64,
+    },
+    GetCooldown {
+        canvas_id: u64,
+        address: String,
+    },
+    GetPalette {},
+    /// Whether the address could draw the pixel at the global coordinates
+    /// right now, and why not if it can't.
+    CanDraw {
+        canvas_id: u64,
+        address: String,
+        x: u64,
+        y: u64,
+    },
+    /// Draw totals for one address on a canvas, zero if they never drew on it.
+    GetPainterStats {
+        canvas_id: u64,
+        address: String,
+    },
+    /// Painters with the most draws on a canvas first.
+    GetLeaderboard {
+        canvas_id: u64,
+        /// Address of the last painter on the previous page.
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetLockedRegions {
+        canvas_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetBanned {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetAllowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// History of a pixel by its global coordinates, newest draw first.
+    GetPixelHistory {
+        canvas_id: u64,
+        x: u64,
+        y: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -48,7 +320,149 @@ pub struct ChunkResponse {
     pub grid: Vec<Vec<PixelInfo>>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAdminResponse {
+    pub pending_admin: Option<PendingAdmin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorRoles {
+    pub address: Addr,
+    pub roles: Vec<Role>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub operators: Vec<OperatorRoles>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RegionResponse {
+    /// One color code per pixel in row-major order.
+    pub colors: Binary,
+    /// The painter of each pixel in the same order, if requested.
+    pub painters: Option<Vec<Option<Addr>>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChunkInfo {
+    pub x: u64,
+    pub y: u64,
+    /// Block height the chunk was last drawn on, not known for chunks
+    /// untouched since 0.1.0.
+    pub last_modified: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChunksResponse {
+    pub chunks: Vec<ChunkInfo>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CooldownResponse {
+    /// Height, or time in time mode, the address can next draw at.
     pub current_cooldown: u64,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CanDrawResponse {
+    pub can_draw: bool,
+    /// Set when the draw would be rejected.
+    pub reason: Option<DrawRejection>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawRejection {
+    Paused {
+        reason: Option<String>,
+    },
+    Banned {},
+    NotOnAllowlist {},
+    /// The pixel is outside the canvas, which is `width` by `height` pixels.
+    OutOfBounds {
+        width: u64,
+        height: u64,
+    },
+    RegionLocked {
+        region_id: u64,
+    },
+    /// Blocks left until the address can draw, or seconds in time mode.
+    Cooldown {
+        remaining: u64,
+    },
+    NotStarted {
+        start_height: u64,
+    },
+    Ended {},
+    /// The address doesn't hold enough to pay for the pixel.
+    InsufficientFunds {
+        required: Coin,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CanvasInfo {
+    pub canvas_id: u64,
+    pub canvas: Canvas,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CanvasesResponse {
+    pub canvases: Vec<CanvasInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PaletteResponse {
+    pub colors: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardEntry {
+    pub address: Addr,
+    pub stats: PainterStats,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardResponse {
+    pub painters: Vec<LeaderboardEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockedRegion {
+    pub region_id: u64,
+    pub region: Region,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockedRegionsResponse {
+    pub regions: Vec<LockedRegion>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BannedResponse {
+    pub addresses: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowlistResponse {
+    pub addresses: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PixelHistoryEntry {
+    /// Index of this draw on the pixel, use as `start_after` to paginate.
+    pub index: u64,
+    pub color: u8,
+    /// Not set when a rollback cleared the pixel.
+    pub painter: Option<Addr>,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PixelHistoryResponse {
+    pub history: Vec<PixelHistoryEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
@@ -1,18 +1,100 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Binary, BlockInfo, Coin, StdResult, Storage, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use cw20::Expiration;
 use cw_storage_plus::{Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
-    /// Admin address, can update config values.
+    /// Admin address, owns the contract and holds every role.
     pub admin_address: Addr,
+    /// How the cooldown is charged for a batch draw.
+    pub batch_cooldown: BatchCooldown,
+    /// Maximum number of pixels that can be drawn in a single batch.
+    pub max_batch_size: u64,
+    /// Price to draw a single pixel, paid in native tokens.
+    /// Optional so if not set drawing is free.
+    pub price: Option<Coin>,
+    /// CW20 tokens that can be sent to pay for drawing instead of native tokens.
+    pub accepted_cw20: Vec<Cw20Price>,
+    /// Number of past draws kept for each pixel, zero disables history.
+    pub history_depth: u64,
+    /// Only addresses on the allowlist can draw when enabled.
+    pub allowlist_enabled: bool,
+    /// Whether cooldowns, start and end heights count blocks or seconds.
+    pub clock_mode: ClockMode,
+    /// No drawing is allowed while paused, queries keep working.
+    pub paused: bool,
+    /// Shown to users while paused.
+    pub pause_reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockMode {
+    /// Counted in blocks, against the block height.
+    Height,
+    /// Counted in seconds, against the block time in unix seconds.
+    Time,
+}
+
+impl ClockMode {
+    pub fn now(&self, block: &BlockInfo) -> u64 {
+        match self {
+            ClockMode::Height => block.height,
+            ClockMode::Time => block.time.seconds(),
+        }
+    }
+}
+
+/// An admin transfer waiting for the new admin to accept it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAdmin {
+    pub address: Addr,
+    pub expires: Expiration,
+}
+
+/// Permissions the admin can hand out to other addresses.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Can lock regions, ban and erase painters and pause drawing.
+    Moderator,
+    /// Can create canvases and change their settings, the palette and the allowlist.
+    ConfigManager,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw20Price {
+    /// Address of the CW20 token contract.
+    pub token: Addr,
+    /// Price to draw a single pixel, in the token's smallest unit.
+    pub price: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchCooldown {
+    /// The cooldown is applied once for every pixel in the batch,
+    /// drawing 5 pixels with a cooldown of 30 means waiting 150 blocks.
+    PerPixel,
+    /// The cooldown is applied once regardless of the batch size.
+    PerBatch,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Canvas {
+    pub dimensions: Dimensions,
     /// Number of blocks between user draws, if set to 30 user
     /// must wait 30 blocks before being able to draw again.
+    /// Counted in seconds instead in time mode.
     pub cooldown: u64,
-    /// Block height the canvas can no longer be drawn on at all.
-    /// Optional so if not set it goes on forever.
+    /// Block height the canvas can first be drawn on, or unix time in time mode.
+    /// Optional so if not set it can be drawn on straight away.
+    pub start_height: Option<u64>,
+    /// Block height the canvas can no longer be drawn on at all, or unix time
+    /// in time mode. Optional so if not set it goes on forever.
     pub end_height: Option<u64>,
 }
 
@@ -22,6 +104,25 @@ pub struct Dimensions {
     pub width: u64,
     /// Number of chunks high
     pub height: u64,
+    /// Number of pixels along each edge of a chunk
+    pub chunk_size: u64,
+}
+
+/// A rectangle of pixels in global pixel coordinates.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Region {
+    /// Left edge, included in the region
+    pub x: u64,
+    /// Top edge, included in the region
+    pub y: u64,
+    pub width: u64,
+    pub height: u64,
+}
+
+impl Region {
+    pub fn contains(&self, x: u64, y: u64) -> bool {
+        x >= self.x && x - self.x < self.width && y >= self.y && y - self.y < self.height
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -31,8 +132,129 @@ pub struct PixelInfo {
     pub painter: Option<Addr>,
 }
 
+/// A chunk packed into bytes, pixels are stored in row-major order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Chunk {
+    /// One byte per pixel holding its color code.
+    pub colors: Binary,
+    /// Four big-endian bytes per pixel holding the id of its painter from
+    /// `PAINTER_IDS`, zero means the pixel has never been painted.
+    pub painter_ids: Binary,
+}
+
+const PAINTER_ID_BYTES: usize = 4;
+
+impl Chunk {
+    /// A chunk of white pixels that nobody has painted.
+    pub fn empty(pixel_count: usize) -> Self {
+        Chunk {
+            colors: Binary(vec![0; pixel_count]),
+            painter_ids: Binary(vec![0; pixel_count * PAINTER_ID_BYTES]),
+        }
+    }
+
+    pub fn color(&self, index: usize) -> u8 {
+        self.colors[index]
+    }
+
+    pub fn painter_id(&self, index: usize) -> Option<u32> {
+        let offset = index * PAINTER_ID_BYTES;
+        let mut painter_id = [0; PAINTER_ID_BYTES];
+        painter_id.copy_from_slice(&self.painter_ids[offset..offset + PAINTER_ID_BYTES]);
+        match u32::from_be_bytes(painter_id) {
+            0 => None,
+            painter_id => Some(painter_id),
+        }
+    }
+
+    pub fn paint(&mut self, index: usize, color: u8, painter_id: u32) {
+        let offset = index * PAINTER_ID_BYTES;
+        self.colors.0[index] = color;
+        self.painter_ids.0[offset..offset + PAINTER_ID_BYTES]
+            .copy_from_slice(&painter_id.to_be_bytes());
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PixelHistory {
+    pub color: u8,
+    /// Zero when a rollback cleared the pixel.
+    pub painter_id: u32,
+    /// Block height the pixel was drawn at.
+    pub height: u64,
+}
+
+/// Totals for one painter on one canvas.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PainterStats {
+    /// Number of pixels drawn, including pixels drawn over more than once.
+    pub total_draws: u64,
+    /// Number of pixels whose latest draw is by this painter.
+    pub pixels_owned: u64,
+    pub first_draw_height: u64,
+    pub last_draw_height: u64,
+}
+
+/// Returns the id for the painter, assigning the next free one on their first draw.
+pub fn intern_painter(storage: &mut dyn Storage, painter: &Addr) -> StdResult<u32> {
+    if let Some(painter_id) = PAINTER_IDS.may_load(storage, painter)? {
+        return Ok(painter_id);
+    }
+
+    // Ids start at one so zero can mean unpainted
+    let painter_id = PAINTER_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    PAINTER_COUNT.save(storage, &painter_id)?;
+    PAINTER_IDS.save(storage, painter, &painter_id)?;
+    PAINTER_ADDRESSES.save(storage, painter_id, painter)?;
+    Ok(painter_id)
+}
+
+/// The 16 colors used before palettes were configurable, index 0 is white.
+pub const DEFAULT_PALETTE: [&str; 16] = [
+    "#FFFFFF", "#E4E4E4", "#888888", "#222222", "#FFA7D1", "#E50000", "#E59500", "#A06A42",
+    "#E5D900", "#94E044", "#02BE01", "#00D3DD", "#0083C7", "#0000EA", "#CF6EE4", "#820080",
+];
+
 pub const CONFIG: Item<Config> = Item::new("config");
-pub const DIMENSIONS: Item<Dimensions> = Item::new("dimensions");
-// A chunk is a 16x16 group of pixels
-pub const CHUNKS: Map<(u64, u64), Vec<Vec<PixelInfo>>> = Map::new("chunks");
-pub const COOLDOWNS: Map<&Addr, u64> = Map::new("cooldowns");
+pub const PENDING_ADMIN: Item<PendingAdmin> = Item::new("pending_admin");
+pub const ROLES: Map<&Addr, Vec<Role>> = Map::new("roles");
+pub const CANVASES: Map<u64, Canvas> = Map::new("canvases");
+// Number of canvases ever created, the id of the next canvas
+pub const CANVAS_COUNT: Item<u64> = Item::new("canvas_count");
+// RGB hex values indexed by color code
+pub const PALETTE: Item<Vec<String>> = Item::new("palette");
+// A chunk is a square group of pixels, chunk_size wide, keyed by (canvas, x, y)
+pub const CHUNKS: Map<(u64, u64, u64), Chunk> = Map::new("packed_chunks");
+// Block height each chunk was last saved at, keyed like `CHUNKS`
+pub const CHUNK_LAST_MODIFIED: Map<(u64, u64, u64), u64> = Map::new("chunk_last_modified");
+// Height, or time in time mode, each painter can next draw on a canvas
+pub const COOLDOWNS: Map<(u64, &Addr), u64> = Map::new("canvas_cooldowns");
+// Compact ids for painters so chunks don't repeat full addresses
+pub const PAINTER_IDS: Map<&Addr, u32> = Map::new("painter_ids");
+pub const PAINTER_ADDRESSES: Map<u32, Addr> = Map::new("painter_addresses");
+pub const PAINTER_COUNT: Item<u32> = Item::new("painter_count");
+// Past draws keyed by (canvas, x, y) in global pixel coordinates and the index of
+// the draw on that pixel
+pub const PIXEL_HISTORY: Map<((u64, u64, u64), u64), PixelHistory> = Map::new("pixel_history");
+// Number of draws ever made on each pixel, the index of the next draw
+pub const PIXEL_DRAW_COUNT: Map<(u64, u64, u64), u64> = Map::new("pixel_draw_count");
+// Color and painter id each pixel had before its first recorded draw, dropped
+// once that draw falls out of the history
+pub const PIXEL_HISTORY_START: Map<(u64, u64, u64), (u8, u32)> = Map::new("pixel_history_start");
+// Stats keyed by (canvas, painter id), only counted from 0.2.0 onwards
+pub const PAINTER_STATS: Map<(u64, u32), PainterStats> = Map::new("painter_stats");
+// Painters ordered by total draws, keyed by (canvas, total draws, painter id)
+pub const PAINTER_RANKING: Map<(u64, u64, u32), ()> = Map::new("painter_ranking");
+// Addresses allowed to draw when the allowlist is enabled
+pub const ALLOWLIST: Map<&Addr, ()> = Map::new("allowlist");
+// Pixels each painter currently owns, keyed by (painter id, (canvas, x, y))
+// in global pixel coordinates. Pixels drawn before 0.2.0 are not indexed.
+pub const PAINTER_PIXELS: Map<(u32, (u64, u64, u64)), ()> = Map::new("painter_pixels");
+// Addresses that can no longer draw
+pub const BANNED: Map<&Addr, ()> = Map::new("banned");
+// Regions nobody can draw on, keyed by (canvas, region id)
+pub const LOCKED_REGIONS: Map<(u64, u64), Region> = Map::new("locked_regions");
+// Number of regions ever locked, the id of the next region
+pub const LOCKED_REGION_COUNT: Item<u64> = Item::new("locked_region_count");
+// CW20 balance collected per token contract, waiting to be withdrawn
+pub const CW20_TREASURY: Map<&Addr, Uint128> = Map::new("cw20_treasury");